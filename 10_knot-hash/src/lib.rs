@@ -1,3 +1,8 @@
+extern crate util;
+
+use util::{ParseError, Solution};
+use util::parse::parse_word;
+
 pub struct Digest(pub [u8; 16]);
 
 impl std::fmt::Display for Digest {
@@ -81,6 +86,37 @@ impl KnotHash {
     }
 }
 
+fn parse_lengths(input : &str) -> Result<Vec<u8>, ParseError> {
+    input.split(|c : char| c == ',' || c.is_whitespace())
+        .filter(|s| ! s.is_empty())
+        .map(|s| parse_word(input, s, "length (0-255)"))
+        .collect()
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const DAY : u32 = 10;
+
+    type Input = String;
+    type Answer1 = usize;
+    type Answer2 = Digest;
+
+    fn parse(input : &str) -> Result<String, String> { Ok(String::from(input.trim())) }
+
+    fn part1(input : &String) -> Result<usize, String> {
+        let lengths = parse_lengths(input)?;
+        let mut knot_hash = KnotHash::new();
+        knot_hash.round(&lengths, &[]);
+        let byte_table = knot_hash.byte_table();
+        Ok((byte_table[0] as usize) * (byte_table[1] as usize))
+    }
+
+    fn part2(input : &String) -> Result<Digest, String> {
+        Ok(KnotHash::new().digest(input.as_bytes()))
+    }
+}
+
 #[test]
 fn test_part_one() {
     let mut input = [0, 1, 2, 3, 4];
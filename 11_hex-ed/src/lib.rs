@@ -0,0 +1,104 @@
+extern crate util;
+
+use util::{ParseError, Solution};
+use util::parse::parse_word;
+
+#[derive(Debug, Clone, Copy)]
+pub enum HexDir {
+    N,
+    NE,
+    SE,
+    S,
+    SW,
+    NW,
+}
+
+use HexDir::*;
+
+impl HexDir {
+    fn apply_step(self, (x, y) : (i32, i32)) -> (i32, i32) {
+        match self {
+            N  => (x    , y + 2),
+            NE => (x + 1, y + 1),
+            SE => (x + 1, y - 1),
+            S  => (x    , y - 2),
+            SW => (x - 1, y - 1),
+            NW => (x - 1, y + 1),
+        }
+    }
+}
+
+use std::str::FromStr;
+
+impl FromStr for HexDir {
+    type Err = String;
+
+    fn from_str(s : &str) -> Result<Self, Self::Err> {
+        let mut si = *b"  ";
+        let len = s.len().min(2);
+        si[..len].copy_from_slice(&s.as_bytes()[..len]);
+        si.make_ascii_lowercase();
+        match &si {
+            b"n " => Ok(N),
+            b"ne" => Ok(NE),
+            b"se" => Ok(SE),
+            b"s " => Ok(S),
+            b"sw" => Ok(SW),
+            b"nw" => Ok(NW),
+            _ => Err(format!("could not parse direction: '{}'", s)),
+        }
+    }
+}
+
+fn distance((x, y) : (i32, i32)) -> i32 {
+    let xd = x.abs();
+    let yd = y.abs();
+    xd + i32::max(0, (yd - xd) / 2)
+}
+
+struct PathDistance {
+    steps : i32,
+    max : i32,
+}
+
+fn path_distance(path : &[HexDir]) -> PathDistance {
+    let mut max = 0;
+    let d = path.iter().fold((0, 0), |mut acc, hd| {
+        acc = hd.apply_step(acc);
+        max = i32::max(max, distance(acc));
+        acc
+    });
+    PathDistance { steps: distance(d), max }
+}
+
+fn parse_input(input : &str) -> Result<Vec<HexDir>, ParseError> {
+    input.split(',').map(str::trim).filter(|s| ! s.is_empty())
+        .map(|s| parse_word(input, s, "direction (n, ne, se, s, sw or nw)"))
+        .collect()
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const DAY : u32 = 11;
+
+    type Input = Vec<HexDir>;
+    type Answer1 = i32;
+    type Answer2 = i32;
+    const LABEL1 : &'static str = "steps";
+    const LABEL2 : &'static str = "max";
+
+    fn parse(input : &str) -> Result<Vec<HexDir>, String> { Ok(parse_input(input)?) }
+
+    fn part1(path : &Vec<HexDir>) -> Result<i32, String> { Ok(path_distance(path).steps) }
+
+    fn part2(path : &Vec<HexDir>) -> Result<i32, String> { Ok(path_distance(path).max) }
+}
+
+#[test]
+fn test_part_1() {
+    assert_eq!(3, path_distance(&[NE, NE, NE]).steps);
+    assert_eq!(0, path_distance(&[NE, NE, SW, SW]).steps);
+    assert_eq!(2, path_distance(&[NE, NE, S, S]).steps);
+    assert_eq!(3, path_distance(&[SE, SW, SE, SW, SW]).steps);
+}
@@ -0,0 +1,84 @@
+#[macro_use] extern crate nom;
+extern crate num;
+extern crate util;
+
+use util::{ParseError, Solution};
+use util::parse::complete;
+
+named!(num<&str, usize>, map_res!(nom::digit, str::parse));
+named!(range<&str, usize>, verify!(num, |r| r > 0));
+named!(parse_layer<&str, (usize, usize)>, ws!(separated_pair!(num, char!(':'), range)));
+
+fn severity(firewall : &[(usize, usize)], delay : usize) -> usize {
+    let mut severity = 0;
+    for &(depth, period) in firewall.iter() {
+        if (depth + delay).is_multiple_of(period) {
+            severity += depth * (period / 2 + 1);
+        }
+    }
+    severity
+}
+
+fn min_delay(firewall : &[(usize, usize)]) -> Option<usize> {
+    use num::Integer;
+
+    let firewall : Vec<(usize, usize)> = firewall.iter().cloned()
+        .map(|(depth, period)| (period, (period - depth % period) % period))
+        .collect();
+
+    let lcm = firewall.iter().cloned().fold(1, |acc, (p, _)| acc.lcm(&p));
+    let gcd = firewall.iter().cloned().fold(lcm, |acc, (p, _)| acc.gcd(&p));
+
+    let mut delay = gcd;
+    while delay < lcm {
+        if ! firewall.iter().cloned().any(|(period, offset)| delay % period == offset) {
+            return Some(delay);
+        }
+        delay += gcd;
+    }
+    None
+}
+
+fn parse_input(input : &str) -> Result<Vec<(usize, usize)>, ParseError> {
+    let mut firewall = Vec::new();
+    for line in input.lines().filter(|s| ! s.trim().is_empty()) {
+        let (depth, range) = complete(input, line, parse_layer(line))?;
+        if range == 1 {
+            let range = line[line.find(':').unwrap() + 1..].trim_start();
+            return Err(ParseError::at(input, range, "range greater than 1 (no delay can pass a range 1 scanner)"));
+        }
+        firewall.push((depth, 2 * (range - 1)));
+    }
+    Ok(firewall)
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const DAY : u32 = 13;
+
+    type Input = Vec<(usize, usize)>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+    const LABEL1 : &'static str = "severity(0)";
+    const LABEL2 : &'static str = "minimum delay";
+
+    fn parse(input : &str) -> Result<Vec<(usize, usize)>, String> { Ok(parse_input(input)?) }
+
+    fn part1(firewall : &Vec<(usize, usize)>) -> Result<usize, String> { Ok(severity(firewall, 0)) }
+
+    fn part2(firewall : &Vec<(usize, usize)>) -> Result<usize, String> {
+        min_delay(firewall).ok_or_else(|| String::from("no solution exists"))
+    }
+}
+
+#[test]
+fn test_example() {
+    let input = parse_input(
+"0: 3
+1: 2
+4: 4
+6: 4").unwrap();
+    assert_eq!(24, severity(&input, 0));
+    assert_eq!(Some(10), min_delay(&input));
+}
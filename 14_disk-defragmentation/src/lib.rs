@@ -0,0 +1,84 @@
+extern crate knot_hash;
+extern crate petgraph;
+extern crate util;
+
+use knot_hash::{KnotHash, Digest};
+use util::{Grid, ParseError, Solution};
+
+/// Which blocks of the 128 x 128 disk are used.
+pub type MemoryMap = Grid<bool>;
+
+fn make_memory_map(khash : &mut KnotHash, input : &str) -> MemoryMap {
+    use std::fmt::Write;
+
+    let mut mem_map = Grid::new(128, 128, false);
+    let mut buffer = String::with_capacity(input.len() + 4);
+    for i in 0..128 {
+        buffer.clear();
+        buffer += input;
+        write!(&mut buffer, "-{}", i).unwrap();
+        let Digest(bytes) = khash.digest(buffer.as_bytes());
+        for (j, used) in mem_map.row_mut(i).iter_mut().enumerate() {
+            *used = bytes[j / 8] & (1 << (7 - j % 8)) != 0;
+        }
+    }
+    mem_map
+}
+
+fn count_regions(mem_map : &MemoryMap) -> usize {
+    use petgraph::unionfind::UnionFind;
+
+    let index = |(x, y) : (usize, usize)| (y * mem_map.width() + x) as u16;
+    let mut regions = UnionFind::<u16>::new(mem_map.cells().len());
+    for block in mem_map.positions().filter(|&block| mem_map[block]) {
+        for neighbor in mem_map.neighbors4(block.0, block.1).filter(|&neighbor| mem_map[neighbor]) {
+            regions.union(index(block), index(neighbor));
+        }
+    }
+    let mut colored_regions = regions.into_labeling();
+    colored_regions.retain(|&block| mem_map.cells()[block as usize]);
+    colored_regions.sort();
+    colored_regions.dedup();
+    colored_regions.len()
+}
+
+fn count_used(mem_map : &MemoryMap) -> u32 {
+    mem_map.cells().iter().filter(|&&used| used).count() as u32
+}
+
+fn parse_input(input : &str) -> Result<MemoryMap, ParseError> {
+    let key = input.trim();
+    if key.is_empty() {
+        return Err(ParseError::at_end(input, "key string"));
+    }
+    if let Some(i) = key.find(char::is_whitespace) {
+        return Err(ParseError::at(input, &key[i..], "end of key string"));
+    }
+    Ok(make_memory_map(&mut KnotHash::new(), key))
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const DAY : u32 = 14;
+
+    type Input = MemoryMap;
+    type Answer1 = u32;
+    type Answer2 = usize;
+    const LABEL1 : &'static str = "blocks used";
+    const LABEL2 : &'static str = "regions";
+
+    fn parse(input : &str) -> Result<MemoryMap, String> { Ok(parse_input(input)?) }
+
+    fn part1(mem_map : &MemoryMap) -> Result<u32, String> { Ok(count_used(mem_map)) }
+
+    fn part2(mem_map : &MemoryMap) -> Result<usize, String> { Ok(count_regions(mem_map)) }
+}
+
+#[test]
+fn test_example() {
+    let input = "flqrgnkx";
+    let mem_map = make_memory_map(&mut KnotHash::new(), input);
+    assert_eq!(8108, count_used(&mem_map));
+    assert_eq!(1242, count_regions(&mem_map));
+}
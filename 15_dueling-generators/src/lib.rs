@@ -0,0 +1,67 @@
+extern crate util;
+
+use util::{ParseError, Progress, Solution};
+
+fn parse_seeds(input : &str) -> Result<(u64, u64), ParseError> {
+    let mut seeds = input.split_whitespace().filter_map(|s| s.parse().ok());
+    match (seeds.next(), seeds.next()) {
+        (Some(a), Some(b)) => Ok((a, b)),
+        _ => Err(ParseError::at_end(input, "two generator seeds")),
+    }
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const DAY : u32 = 15;
+
+    type Input = (u64, u64);
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input : &str) -> Result<(u64, u64), String> { Ok(parse_seeds(input)?) }
+
+    fn part1(&(a, b) : &(u64, u64)) -> Result<usize, String> { matching_pairs(a, b, 40_000_000) }
+
+    fn part2(&(a, b) : &(u64, u64)) -> Result<usize, String> { matching_pairs_2(a, b, 5_000_000) }
+}
+
+fn matching_pairs(mut a : u64, mut b : u64, iterations : usize) -> Result<usize, String> {
+    let mut progress = Progress::new("judging", Some(iterations as u64));
+    let mut matches = 0;
+    for i in 0..iterations {
+        progress.update(i as u64).map_err(|t| t.with_partial(format!("{} matches", matches)))?;
+        a = (a * 16807) % 2147483647;
+        b = (b * 48271) % 2147483647;
+        if a & 0xffff == b & 0xffff {
+            matches += 1;
+        }
+    }
+    Ok(matches)
+}
+
+fn matching_pairs_2(mut a : u64, mut b : u64, iterations : usize) -> Result<usize, String> {
+    let mut progress = Progress::new("judging", Some(iterations as u64));
+    let mut matches = 0;
+    for i in 0..iterations {
+        progress.update(i as u64).map_err(|t| t.with_partial(format!("{} matches", matches)))?;
+        a = (a * 16807) % 2147483647;
+        while ! a.is_multiple_of(4) {
+            a = (a * 16807) % 2147483647;
+        }
+        b = (b * 48271) % 2147483647;
+        while ! b.is_multiple_of(8) {
+            b = (b * 48271) % 2147483647;
+        }
+        if a & 0xffff == b & 0xffff {
+            matches += 1;
+        }
+    }
+    Ok(matches)
+}
+
+#[test]
+fn test_part_one() {
+    assert_eq!(Ok(588), matching_pairs(65, 8921, 40_000_000));
+    assert_eq!(Ok(309), matching_pairs_2(65, 8921, 5_000_000));
+}
@@ -0,0 +1,138 @@
+#[macro_use] extern crate nom;
+extern crate util;
+
+use util::{ParseError, Solution};
+use util::cycle;
+use util::parse::complete;
+
+named!(num<&str, usize>, map_res!(nom::digit, str::parse));
+named!(spin<&str, DanceMove>, do_parse!(
+        char!('s') >>
+        s: num     >>
+        (Spin(s))
+        ));
+named!(exchange<&str, DanceMove>, do_parse!(
+        char!('x') >>
+        a: num     >>
+        char!('/') >>
+        b: num     >>
+        (Exchange(a, b))
+        ));
+named!(letter<&str, char>, verify!(nom::anychar, |c : char| c.is_ascii_lowercase()));
+named!(partner<&str, DanceMove>, do_parse!(
+        char!('p')    >>
+        a: letter     >>
+        char!('/')    >>
+        b: letter     >>
+        (Partner(a as u8, b as u8))
+        ));
+named!(dance_move<&str, DanceMove>, alt!(spin | exchange | partner));
+
+fn parse_input(input : &str) -> Result<Vec<DanceMove>, ParseError> {
+    input.trim_end().split(',').map(|s| complete(input, s, dance_move(s))).collect()
+}
+
+#[derive(Clone, Copy)]
+pub enum DanceMove {
+    Spin(usize),
+    Exchange(usize, usize),
+    Partner(u8, u8),
+}
+use DanceMove::*;
+
+mod dance_line {
+    use DanceMove;
+    use DanceMove::*;
+
+    #[derive(Clone)]
+    pub struct DanceLine {
+        line : [u8; 16],
+        size : usize,
+    }
+
+    const START : [u8; 16] = *b"abcdefghijklmnop";
+
+    impl DanceLine {
+        pub fn new(size : usize) -> DanceLine {
+            assert!(size <= 16);
+            DanceLine { line: START, size }
+        }
+
+        pub fn dance(&mut self, dance : &[DanceMove]) {
+            for &mov in dance.iter() {
+                match mov {
+                    Spin(len) => {
+                        let temp = self.line;
+                        let pos = self.size - len;
+                        self.line[..len].copy_from_slice(&temp[pos..self.size]);
+                        self.line[len..self.size].copy_from_slice(&temp[..pos]);
+                    },
+                    Exchange(a, b) => self.line[..self.size].swap(a, b),
+                    Partner(a, b) => {
+                        let ia = self.line[..self.size].iter().position(|&c| c == a).unwrap();
+                        let ib = self.line[..self.size].iter().position(|&c| c == b).unwrap();
+                        self.line.swap(ia, ib)
+                    }
+                }
+            }
+        }
+
+        pub fn as_str(&self) -> &str { unsafe { ::std::str::from_utf8_unchecked(&self.line[..self.size]) } }
+    }
+
+    impl PartialEq for DanceLine {
+        fn eq(&self, other : &DanceLine) -> bool { self.line == other.line }
+    }
+}
+
+use dance_line::DanceLine;
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const DAY : u32 = 16;
+
+    type Input = Vec<DanceMove>;
+    type Answer1 = String;
+    type Answer2 = String;
+    const LABEL1 : &'static str = "first dance";
+    const LABEL2 : &'static str = "billionth dance";
+
+    fn parse(input : &str) -> Result<Vec<DanceMove>, String> { Ok(parse_input(input)?) }
+
+    fn part1(moves : &Vec<DanceMove>) -> Result<String, String> {
+        let mut line = DanceLine::new(16);
+        line.dance(moves);
+        Ok(String::from(line.as_str()))
+    }
+
+    fn part2(moves : &Vec<DanceMove>) -> Result<String, String> {
+        let mut line = DanceLine::new(16);
+        let cycle = cycle::brent(line.clone(), |line| line.dance(moves));
+        for _ in 0..cycle.index(1_000_000_000) {
+            line.dance(moves);
+        }
+        Ok(String::from(line.as_str()))
+    }
+}
+
+#[test]
+fn test_part_1() {
+    let mut line = DanceLine::new(5);
+    line.dance(&[Spin(1)]);
+    assert_eq!("eabcd", line.as_str());
+    line.dance(&[Exchange(3, 4)]);
+    assert_eq!("eabdc", line.as_str());
+    line.dance(&[Partner(b'e', b'b')]);
+    assert_eq!("baedc", line.as_str());
+}
+
+#[test]
+fn test_part_2() {
+    let moves = parse_input("s1,x3/4,pe/b").unwrap();
+    let mut line = DanceLine::new(5);
+    line.dance(&moves);
+    assert_eq!("baedc", line.as_str());
+    line.dance(&moves);
+    assert_eq!("ceadb", line.as_str());
+}
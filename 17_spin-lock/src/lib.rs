@@ -0,0 +1,60 @@
+extern crate util;
+
+use util::{ParseError, Progress, Solution};
+use util::parse::parse_word;
+
+fn parse_input(input : &str) -> Result<usize, ParseError> {
+    parse_word(input, input.trim(), "step count")
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const DAY : u32 = 17;
+
+    type Input = usize;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input : &str) -> Result<usize, String> { Ok(parse_input(input)?) }
+
+    fn part1(&steps : &usize) -> Result<u32, String> { Ok(simulate(steps)) }
+
+    fn part2(&steps : &usize) -> Result<u32, String> { simulate_after_zero(steps) }
+}
+
+fn simulate(step : usize) -> u32 {
+    let mut buffer : Vec<u32> = Vec::with_capacity(2018);
+    buffer.push(0);
+    let mut pos = 0;
+    for i in 1..2018 {
+        pos = (pos + step + 1) % i;
+        buffer.insert(pos + 1, i as u32);
+    }
+    let pos = buffer.iter().position(|&n| n == 2017).unwrap();
+    if pos < 2017 {
+        buffer[pos + 1]
+    } else {
+        buffer[0]
+    }
+}
+
+fn simulate_after_zero(step : usize) -> Result<u32, String> {
+    const INSERTIONS : usize = 50_000_000;
+    let mut progress = Progress::new("spinning", Some(INSERTIONS as u64));
+    let mut pos = 0;
+    let mut val = 0;
+    for i in 1..(INSERTIONS + 1) {
+        progress.update(i as u64).map_err(|t| t.with_partial(format!("{} after 0", val)))?;
+        pos = (pos + step + 1) % i;
+        if pos == 0 {
+            val = i as u32;
+        }
+    }
+    Ok(val)
+}
+
+#[test]
+fn test_part_1() {
+    assert_eq!(638, simulate(3));
+}
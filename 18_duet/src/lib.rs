@@ -0,0 +1,373 @@
+extern crate util;
+extern crate vm;
+
+pub mod network;
+pub mod threads;
+
+use std::fmt;
+
+use util::{ParseError, Solution};
+use vm::{Compile, Config, Effect, Encode, InstructionSet, Machine, Operands, Program, Registers, Termination, Watchdog};
+use vm::arith::{Arithmetic, BinOp};
+use vm::bytecode::Reader;
+use vm::compile::{self, Op};
+
+pub use network::{Network, Outcome, Status, Topology};
+pub use threads::run_threads;
+
+pub use vm::{Register, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Instruction {
+    Snd(Value),
+    Set(Register, Value),
+    Add(Register, Value),
+    Mul(Register, Value),
+    Mod(Register, Value),
+    Rcv(Register),
+    Jgz(Value, Value),
+}
+use Instruction::*;
+
+impl InstructionSet for Instruction {
+    const REGISTERS : usize = 26;
+
+    fn decode(args : &Operands) -> Result<Instruction, ParseError> {
+        Ok(match args.mnemonic() {
+            "snd" => Snd(args.value(0)?),
+            "set" => Set(args.register(0)?, args.value(1)?),
+            "add" => Add(args.register(0)?, args.value(1)?),
+            "mul" => Mul(args.register(0)?, args.value(1)?),
+            "mod" => Mod(args.register(0)?, args.value(1)?),
+            "rcv" => Rcv(args.register(0)?),
+            "jgz" => Jgz(args.value(0)?, args.offset(1)?),
+            _ => return Err(args.unknown()),
+        })
+    }
+
+    fn execute(&self, registers : &mut Registers) -> Effect {
+        match *self {
+            Snd(x) => return Effect::Send(registers.get(x)),
+            Set(x, y) => {
+                let y = registers.get(y);
+                registers.set(x, y);
+            },
+            Add(x, y) => return registers.add(x, y),
+            Mul(x, y) => return registers.mul(x, y),
+            Mod(x, y) => return registers.modulus(x, y),
+            Rcv(r) => return Effect::Receive(r),
+            Jgz(x, y) => if registers.get(x) > 0 {
+                return Effect::Jump(registers.get(y));
+            },
+        }
+        Effect::Next
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Snd(x) => write!(f, "snd {}", x),
+            Set(x, y) => write!(f, "set {} {}", x, y),
+            Add(x, y) => write!(f, "add {} {}", x, y),
+            Mul(x, y) => write!(f, "mul {} {}", x, y),
+            Mod(x, y) => write!(f, "mod {} {}", x, y),
+            Rcv(r) => write!(f, "rcv {}", r),
+            Jgz(x, y) => write!(f, "jgz {} {}", x, y),
+        }
+    }
+}
+
+impl Encode for Instruction {
+    const NAME : &'static str = "duet";
+
+    fn encode(&self) -> (u8, Vec<Value>) {
+        match *self {
+            Snd(x) => (0, vec![x]),
+            Set(x, y) => (1, vec![Value::Reg(x), y]),
+            Add(x, y) => (2, vec![Value::Reg(x), y]),
+            Mul(x, y) => (3, vec![Value::Reg(x), y]),
+            Mod(x, y) => (4, vec![Value::Reg(x), y]),
+            Rcv(r) => (5, vec![Value::Reg(r)]),
+            Jgz(x, y) => (6, vec![x, y]),
+        }
+    }
+
+    fn decode_bytes(opcode : u8, args : &mut Reader) -> Result<Instruction, String> {
+        Ok(match opcode {
+            0 => Snd(args.value()?),
+            1 => Set(args.register()?, args.value()?),
+            2 => Add(args.register()?, args.value()?),
+            3 => Mul(args.register()?, args.value()?),
+            4 => Mod(args.register()?, args.value()?),
+            5 => Rcv(args.register()?),
+            6 => Jgz(args.value()?, args.value()?),
+            _ => return Err(args.unknown()),
+        })
+    }
+}
+
+impl Compile for Instruction {
+    fn compile(&self, ip : i64, mode : Arithmetic) -> Op {
+        match *self {
+            Snd(x) => compile::send(x),
+            Set(x, y) => compile::set(x, y),
+            Add(x, y) => compile::binary(x, y, BinOp::Add, mode),
+            Mul(x, y) => compile::binary(x, y, BinOp::Mul, mode),
+            Mod(x, y) => compile::binary(x, y, BinOp::Mod, mode),
+            Rcv(_) => compile::interpret(*self, ip),
+            Jgz(x, y) => compile::jump_if(x, |x| x > 0, y, ip),
+        }
+    }
+}
+
+pub fn parse_program(input : &str) -> Result<Vec<Instruction>, ParseError> {
+    vm::parse_program(input)
+}
+
+type Process<'a> = vm::Process<'a, Instruction>;
+use vm::State::*;
+
+fn new_process(pid : i64, program : &[Instruction], config : Config) -> Process<'_> {
+    let mut process = Process::with_config(program, config);
+    process.set_register('p', pid);
+    process
+}
+
+/// Part one: `snd` plays a sound, and `rcv` recovers the last sound played
+/// unless its register is 0.
+struct Solo<'a> {
+    process : Process<'a>,
+    freq : i64,
+    watchdog : Watchdog,
+}
+
+impl<'a> Solo<'a> {
+    fn new(program : &'a [Instruction], config : Config) -> Solo<'a> {
+        let process = new_process(0, program, config);
+        Solo { watchdog: Watchdog::for_process(&process), process, freq: 0 }
+    }
+
+    /// Runs one step, returning the recovered frequency once there is one, or
+    /// why there won't be (see `vm::watchdog`).
+    fn step(&mut self) -> Option<Result<Termination<i64>, String>> {
+        if let Some(termination) = self.watchdog.check(&self.process) {
+            return Some(Ok(termination));
+        }
+        match self.process.step() {
+            Ran(_) => {},
+            Sending(f) => {
+                self.freq = f;
+                self.watchdog.io();
+            },
+            Waiting(r) => {
+                let x = self.process.get_register(r);
+                if x != 0 {
+                    return Some(Ok(Termination::Halted(self.freq)));
+                }
+                self.process.receive(r, x);
+            },
+            Halted(ip) => return Some(Err(format!("invalid instruction: {}", ip))),
+            Faulted(ip, fault) => return Some(Ok(Termination::Faulted { ip, fault })),
+        }
+        None
+    }
+}
+
+impl<'a> Machine<'a> for Solo<'a> {
+    type Instruction = Instruction;
+
+    fn tick(&mut self) -> Option<String> {
+        self.step().map(|result| result.and_then(Termination::into_result)
+                        .map_or_else(|e| e, |freq| format!("recovered frequency {}", freq)))
+    }
+
+    fn processes(&self) -> Vec<&Process<'a>> { vec![&self.process] }
+
+    fn processes_mut(&mut self) -> Vec<&mut Process<'a>> { vec![&mut self.process] }
+
+    fn describe(&self) -> Vec<String> { vec![format!("last sound: {}", self.freq)] }
+}
+
+fn run_solo(program : &[Instruction], config : Config) -> Result<Termination<i64>, String> {
+    let mut solo = Solo::new(program, config);
+    loop {
+        if let Some(result) = solo.step() {
+            return result;
+        }
+    }
+}
+
+#[allow(dead_code)]
+fn run_duet_single(program : &[Instruction], config : Config) -> Result<usize, String> {
+    let mut duet = Network::with_config(program, 2, &Topology::Pairs, config)?;
+    if let Outcome::Stopped(pid, termination) = duet.run() {
+        return Err(format!("process {}: {}", pid, termination.into_result().unwrap_err()));
+    }
+    for (pid, &status) in duet.status().iter().enumerate() {
+        if let Status::Faulted(ip, fault) = status {
+            return Err(format!("{} at line {} of process {}", fault, ip + 1, pid));
+        }
+    }
+    Ok(duet.sends()[1])
+}
+
+/// Runs `n` processes connected by `topology` (by default, as many as the
+/// topology needs) until none can go on, and reports how many values each sent.
+/// With `threads`, each process runs on its own thread (see `run_threads`).
+pub fn run_network(program : &[Instruction], n : Option<usize>, topology : &Topology, threads : bool, config : Config) -> Result<String, String> {
+    let n = n.or_else(|| topology.size()).unwrap_or(2);
+    if threads {
+        let sends = run_threads(program, n, topology, config)?;
+        let lines : Vec<String> = sends.iter().enumerate().map(|(pid, sent)| format!("p{}: sent {}", pid, sent)).collect();
+        return Ok(lines.join("\n"));
+    }
+    let mut network = Network::with_config(program, n, topology, config)?;
+    let outcome = match network.run() {
+        Outcome::Deadlock => String::from("deadlock: every process is waiting for a value or has halted"),
+        Outcome::Halted => String::from("every process halted"),
+        Outcome::Stopped(pid, termination) => format!("process {}: {}", pid, termination.into_result().unwrap_err()),
+    };
+    let mut lines = network.summary();
+    lines.push(outcome);
+    Ok(lines.join("\n"))
+}
+
+/// Runs the program under the debugger, as in part one or as the two
+/// processes of part two.
+pub fn debug(program : &[Instruction], part2 : bool, config : Config) {
+    if part2 {
+        vm::debug::debug(Network::with_config(program, 2, &Topology::Pairs, config).unwrap());
+    } else {
+        vm::debug::debug(Solo::new(program, config));
+    }
+}
+
+/// Runs part one, or both processes of part two, for at most `max_steps`
+/// steps, and reports how often each line ran (see `vm::trace::profile`).
+pub fn profile(program : &[Instruction], part2 : bool, max_steps : Option<u64>, trace_file : Option<&str>, config : Config) -> Result<String, String> {
+    if part2 {
+        vm::trace::profile(&mut Network::with_config(program, 2, &Topology::Pairs, config).unwrap(), max_steps, trace_file)
+    } else {
+        vm::trace::profile(&mut Solo::new(program, config), max_steps, trace_file)
+    }
+}
+
+fn run_duet_multi(program : &[Instruction], config : Config) -> Result<i64, String> {
+    Ok(run_threads(program, 2, &Topology::Pairs, config)?[1] as i64)
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const DAY : u32 = 18;
+
+    type Input = Program<Instruction>;
+    type Answer1 = i64;
+    type Answer2 = i64;
+    const LABEL1 : &'static str = "last frequency";
+    const LABEL2 : &'static str = "sends for p1";
+
+    fn parse(input : &str) -> Result<Program<Instruction>, String> { Ok(Program::new(parse_program(input)?)) }
+
+    fn part1(program : &Program<Instruction>) -> Result<i64, String> {
+        run_solo(&program.instructions, program.config)?.into_result()
+    }
+
+    fn part2(program : &Program<Instruction>) -> Result<i64, String> { run_duet_multi(&program.instructions, program.config) }
+}
+
+#[test]
+fn test_part_1() {
+    let input =
+"set a 1
+add a 2
+mul a a
+mod a 5
+snd a
+set a 0
+rcv a
+jgz a -1
+set a 1
+jgz a -2";
+    let program = parse_program(input).unwrap();
+    assert_eq!(Ok(Termination::Halted(4)), run_solo(&program, Config::default()));
+}
+
+#[test]
+fn test_part_2() {
+    let input =
+"snd 1
+snd 2
+snd p
+rcv a
+rcv b
+rcv c
+rcv d";
+    let program = parse_program(input).unwrap();
+    assert_eq!(Ok(3), run_duet_single(&program, Config::default()));
+    assert_eq!(Ok(3), run_duet_multi(&program, Config::default()));
+}
+
+#[test]
+fn test_compiled() {
+    use vm::compile::Stop;
+
+    let program = parse_program("set a 1\nadd a 2\nmul a a\nmod a 5\nsnd a\nset b a\njgz b 2\nsnd 7\nrcv c\njgz 1 b").unwrap();
+    let code = vm::Compiled::new(&program);
+    let mut runner = vm::Runner::new(&code);
+    assert_eq!(runner.run(100), Stop::Sending(4));
+    assert_eq!(runner.run(100), Stop::Waiting('c'));
+    runner.receive('c', 5);
+    assert_eq!(runner.run(100), Stop::Halted(13));
+    assert_eq!((runner.get_register('b'), runner.get_register('c'), runner.steps()), (4, 5, 9));
+}
+
+#[test]
+fn test_round_trip() {
+    let input = "snd 1\nset a p\nadd b -2\nmul c a\nmod a 5\nrcv z\njgz p -6\n";
+    let program = parse_program(input).unwrap();
+    assert_eq!(vm::disassemble(&program), input);
+    assert_eq!(parse_program(&vm::disassemble(&program)), Ok(program.clone()));
+    assert_eq!(vm::bytecode::from_bytes(&vm::bytecode::to_bytes(&program)), Ok(program));
+}
+
+#[test]
+fn test_labels() {
+    let input =
+"set a 1
+add a 2      ; a = 3
+    mul a a
+    mod a 5
+    snd a
+    set a 0
+recover: rcv a
+again:
+    jgz a recover
+    set a 1
+    jgz a again";
+    let program = parse_program(input).unwrap();
+    assert_eq!(vm::disassemble(&program[6..]), "rcv a\njgz a -1\nset a 1\njgz a -2\n");
+    assert_eq!(Ok(Termination::Halted(4)), run_solo(&program, Config::default()));
+}
+
+#[test]
+fn test_faults() {
+    let program = parse_program("set a 5\nsnd a\nmod a b\nrcv a").unwrap();
+    assert_eq!(run_solo(&program, Config::default()).and_then(Termination::into_result), Err(String::from("mod by zero at line 3")));
+    assert_eq!(run_duet_single(&program, Config::default()), Err(String::from("mod by zero at line 3 of process 0")));
+    assert_eq!(run_threads(&program, 2, &Topology::Pairs, Config::default()), Err(String::from("process 0: mod by zero at line 3\n\
+                                                                            process 1: mod by zero at line 3")));
+
+    let program = parse_program("set a 1\njgz a 9223372036854775807").unwrap();
+    assert_eq!(run_solo(&program, Config::default()).and_then(Termination::into_result), Err(String::from("jump target overflow at line 2")));
+    assert_eq!(run_duet_single(&program, Config::default()), Err(String::from("jump target overflow at line 2 of process 0")));
+}
+
+#[test]
+fn test_parse_error() {
+    let e = parse_program("set a 1\nadd a 2\nmul a !\n").unwrap_err();
+    assert_eq!((e.line, e.column, e.snippet.as_str()), (3, 7, "mul a !"));
+    let e = parse_program("set a 1\njmp a 2\n").unwrap_err();
+    assert_eq!((e.line, e.column, e.expected.as_str()), (2, 1, "known keyword"));
+}
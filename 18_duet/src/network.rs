@@ -0,0 +1,319 @@
+//! Runs any number of duet processes, connected by message queues in a given
+//! topology. Process `i` starts with `i` in register `p`, like the two
+//! processes of part two.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::Read;
+
+use util::ParseError;
+use util::parse::parse_word;
+use vm::{Config, Fault, Machine, Termination, Watchdog};
+
+use {Instruction, Process, new_process};
+use vm::State::*;
+
+/// Where the values sent by each process go.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Topology {
+    /// Processes 0 and 1 send to each other, as do 2 and 3, and so on.
+    Pairs,
+    /// Each process sends to the next one, and the last to the first.
+    Ring,
+    /// Each process sends to every other process.
+    Broadcast,
+    /// Process `i` sends to every process in the `i`th list.
+    Custom(Vec<Vec<usize>>),
+}
+
+impl Topology {
+    /// Parses the name of a topology, or reads a custom one from a file (see
+    /// `parse_config`).
+    pub fn from_arg(arg : &str) -> Result<Topology, String> {
+        match arg {
+            "pairs" => Ok(Topology::Pairs),
+            "ring" => Ok(Topology::Ring),
+            "broadcast" => Ok(Topology::Broadcast),
+            path => {
+                let mut config = String::new();
+                File::open(path).and_then(|mut file| file.read_to_string(&mut config))
+                    .map_err(|e| format!("can't read topology {}: {}", path, e))?;
+                Ok(Topology::parse_config(&config)?)
+            },
+        }
+    }
+
+    /// Parses a custom topology with one line for each process that sends,
+    /// like `0 -> 1 2`. Anything after `#` is a comment.
+    pub fn parse_config(input : &str) -> Result<Topology, ParseError> {
+        let mut targets : Vec<Vec<usize>> = Vec::new();
+        for line in input.lines() {
+            let line = line.split('#').next().unwrap();
+            if line.trim().is_empty() {
+                continue;
+            }
+            let arrow = line.find("->").ok_or_else(|| ParseError::at(input, &line[line.len()..], "'->'"))?;
+            let from : usize = parse_word(input, line[..arrow].trim(), "process number")?;
+            let to = line[arrow + 2..].split_whitespace()
+                .map(|word| parse_word(input, word, "process number"))
+                .collect::<Result<Vec<usize>, ParseError>>()?;
+            if targets.len() <= from {
+                targets.resize(from + 1, Vec::new());
+            }
+            targets[from].extend(to);
+        }
+        Ok(Topology::Custom(targets))
+    }
+
+    /// Number of processes the topology needs, if it has a fixed size.
+    pub fn size(&self) -> Option<usize> {
+        match *self {
+            Topology::Custom(ref targets) => {
+                let max = targets.iter().flatten().cloned().chain(Some(targets.len().saturating_sub(1))).max();
+                max.map(|max| max + 1)
+            },
+            _ => None,
+        }
+    }
+
+    /// Where each of `n` processes sends its values.
+    pub fn targets(&self, n : usize) -> Result<Vec<Vec<usize>>, String> {
+        match *self {
+            Topology::Pairs if n % 2 == 1 => Err(format!("can't pair up {} processes", n)),
+            Topology::Pairs => Ok((0..n).map(|i| vec![i ^ 1]).collect()),
+            Topology::Ring => Ok((0..n).map(|i| vec![(i + 1) % n]).collect()),
+            Topology::Broadcast => Ok((0..n).map(|i| (0..n).filter(|&j| j != i).collect()).collect()),
+            Topology::Custom(ref targets) => {
+                if let Some(&to) = targets.iter().flatten().find(|&&to| to >= n) {
+                    return Err(format!("topology sends to process {}, but there are only {}", to, n));
+                }
+                let mut targets = targets.clone();
+                targets.resize(n, Vec::new());
+                Ok(targets)
+            },
+        }
+    }
+}
+
+/// What a process in a network is doing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Running,
+    /// Waiting for a value with nothing in its queue.
+    Waiting,
+    /// Jumped outside the program, to the instruction pointer.
+    Halted(i64),
+    /// Couldn't execute the instruction at the instruction pointer.
+    Faulted(i64, Fault),
+}
+
+impl Status {
+    /// True if the process won't run again.
+    pub fn is_stopped(self) -> bool { matches!(self, Status::Halted(_) | Status::Faulted(..)) }
+}
+
+/// Why a network stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Every process that hasn't stopped is waiting for a value.
+    Deadlock,
+    /// Every process has halted or faulted.
+    Halted,
+    /// The watchdog of the process stopped it (see `vm::watchdog`), and with it
+    /// the network.
+    Stopped(usize, Termination<()>),
+}
+
+/// Processes running the same program, each with a queue of values sent to it.
+pub struct Network<'a> {
+    processes : Vec<Process<'a>>,
+    queues : Vec<VecDeque<i64>>,
+    status : Vec<Status>,
+    sends : Vec<usize>,
+    targets : Vec<Vec<usize>>,
+    watchdogs : Vec<Watchdog>,
+    stopped : Option<(usize, Termination<()>)>,
+}
+
+impl<'a> Network<'a> {
+    pub fn new(program : &'a [Instruction], n : usize, topology : &Topology) -> Result<Network<'a>, String> {
+        Network::with_config(program, n, topology, Config::default())
+    }
+
+    /// The network, with every process running with `config`.
+    pub fn with_config(program : &'a [Instruction], n : usize, topology : &Topology, config : Config) -> Result<Network<'a>, String> {
+        let processes : Vec<Process<'a>> = (0..n).map(|pid| new_process(pid as i64, program, config)).collect();
+        Ok(Network {
+            watchdogs: processes.iter().map(Watchdog::for_process).collect(),
+            processes,
+            queues: vec![VecDeque::new(); n],
+            status: vec![Status::Running; n],
+            sends: vec![0; n],
+            targets: topology.targets(n)?,
+            stopped: None,
+        })
+    }
+
+    /// Runs one step of every process that hasn't halted, returning why the
+    /// network stopped if no process can go on, or one ran out of steps or
+    /// went into a loop.
+    pub fn step(&mut self) -> Option<Outcome> {
+        if let Some((pid, termination)) = self.stopped {
+            return Some(Outcome::Stopped(pid, termination));
+        }
+        for pid in 0..self.processes.len() {
+            if self.status[pid].is_stopped() {
+                continue;
+            }
+            // a waiting process only runs again once there is a value for it,
+            // and its watchdog has already checked that step
+            if self.status[pid] == Status::Waiting {
+                if self.queues[pid].is_empty() {
+                    continue;
+                }
+            } else if let Some(termination) = self.watchdogs[pid].check(&self.processes[pid]) {
+                self.stopped = Some((pid, termination));
+                return Some(Outcome::Stopped(pid, termination));
+            }
+            match self.processes[pid].step() {
+                Ran(_) => self.status[pid] = Status::Running,
+                Sending(x) => {
+                    self.watchdogs[pid].io();
+                    self.sends[pid] += 1;
+                    for &to in &self.targets[pid] {
+                        self.queues[to].push_back(x);
+                    }
+                    self.status[pid] = Status::Running;
+                },
+                Waiting(r) => if let Some(x) = self.queues[pid].pop_front() {
+                    self.processes[pid].receive(r, x);
+                    self.watchdogs[pid].io();
+                    self.status[pid] = Status::Running;
+                } else {
+                    self.status[pid] = Status::Waiting;
+                },
+                Halted(ip) => self.status[pid] = Status::Halted(ip),
+                Faulted(ip, fault) => self.status[pid] = Status::Faulted(ip, fault),
+            }
+        }
+        let stuck = |pid : usize| match self.status[pid] {
+            Status::Running => false,
+            Status::Waiting => self.queues[pid].is_empty(),
+            Status::Halted(_) | Status::Faulted(..) => true,
+        };
+        if ! (0..self.processes.len()).all(stuck) {
+            None
+        } else if self.status.iter().all(|s| s.is_stopped()) {
+            Some(Outcome::Halted)
+        } else {
+            Some(Outcome::Deadlock)
+        }
+    }
+
+    /// Runs until no process can go on.
+    pub fn run(&mut self) -> Outcome {
+        loop {
+            if let Some(outcome) = self.step() {
+                return outcome;
+            }
+        }
+    }
+
+    /// Number of values each process has sent.
+    pub fn sends(&self) -> &[usize] { &self.sends }
+
+    pub fn status(&self) -> &[Status] { &self.status }
+
+    pub fn processes(&self) -> &[Process<'a>] { &self.processes }
+
+    /// One line for each process with its queue, sends and status.
+    pub fn summary(&self) -> Vec<String> {
+        (0..self.processes.len()).map(|pid| {
+            let status = match self.status[pid] {
+                Status::Running => String::new(),
+                Status::Waiting => String::from(", waiting"),
+                Status::Halted(ip) => format!(", halted at {}", ip),
+                Status::Faulted(ip, fault) => format!(", {} at line {}", fault, ip + 1),
+            };
+            format!("p{}: queue {}, sent {}{}", pid, vm::debug::format_values(self.queues[pid].iter().cloned()), self.sends[pid], status)
+        }).collect()
+    }
+}
+
+impl<'a> Machine<'a> for Network<'a> {
+    type Instruction = Instruction;
+
+    fn tick(&mut self) -> Option<String> {
+        self.step().map(|outcome| match outcome {
+            Outcome::Deadlock => format!("deadlock, sends {}", vm::debug::format_values(self.sends.iter().map(|&s| s as i64))),
+            Outcome::Halted => String::from("every process halted"),
+            Outcome::Stopped(pid, termination) => format!("process {}: {}", pid, termination.into_result().unwrap_err()),
+        })
+    }
+
+    fn processes(&self) -> Vec<&Process<'a>> { self.processes.iter().collect() }
+
+    fn processes_mut(&mut self) -> Vec<&mut Process<'a>> { self.processes.iter_mut().collect() }
+
+    fn describe(&self) -> Vec<String> { self.summary() }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parse_program;
+
+    #[test]
+    fn test_topologies() {
+        let program = parse_program("snd p\nrcv a\nrcv b").unwrap();
+        let mut ring = Network::new(&program, 3, &Topology::Ring).unwrap();
+        assert_eq!(ring.run(), Outcome::Deadlock);
+        assert_eq!(ring.sends(), &[1, 1, 1]);
+        let received : Vec<i64> = ring.processes().iter().map(|p| p.get_register('a')).collect();
+        assert_eq!(received, vec![2, 0, 1]);
+
+        let mut broadcast = Network::new(&program, 3, &Topology::Broadcast).unwrap();
+        assert_eq!(broadcast.run(), Outcome::Halted);
+        let received : Vec<(i64, i64)> = broadcast.processes().iter().map(|p| (p.get_register('a'), p.get_register('b'))).collect();
+        assert_eq!(received, vec![(1, 2), (0, 2), (0, 1)]);
+
+        assert!(Network::new(&program, 3, &Topology::Pairs).is_err());
+    }
+
+    #[test]
+    fn test_config() {
+        let topology = Topology::parse_config("# a star\n0 -> 1 2 3\n\n1 -> 0\n2 -> 0 # back to the center\n").unwrap();
+        assert_eq!(topology, Topology::Custom(vec![vec![1, 2, 3], vec![0], vec![0]]));
+        assert_eq!(topology.size(), Some(4));
+        assert_eq!(topology.targets(4).unwrap()[3], Vec::<usize>::new());
+        assert!(topology.targets(3).is_err());
+
+        let program = parse_program("snd p\nrcv a\nrcv b").unwrap();
+        let mut star = Network::new(&program, 4, &topology).unwrap();
+        assert_eq!(star.run(), Outcome::Deadlock);
+        assert_eq!(star.status(), &[Status::Halted(3), Status::Waiting, Status::Waiting, Status::Waiting]);
+
+        let e = Topology::parse_config("0 -> 1\n1 => 0").unwrap_err();
+        assert_eq!((e.line, e.column, e.expected.as_str()), (2, 7, "'->'"));
+        let e = Topology::parse_config("0 -> x").unwrap_err();
+        assert_eq!((e.line, e.column, e.expected.as_str()), (1, 6, "process number"));
+    }
+
+    #[test]
+    fn test_watchdog() {
+        let program = parse_program("jgz 1 0").unwrap();
+        let config = Config { limits: vm::Limits { max_steps: Some(10), detect_loops: false }, ..Config::default() };
+        let mut network = Network::with_config(&program, 2, &Topology::Pairs, config).unwrap();
+        assert_eq!(network.run(), Outcome::Stopped(0, Termination::Exhausted { steps: 10 }));
+        assert_eq!(network.step(), Some(Outcome::Stopped(0, Termination::Exhausted { steps: 10 })));
+        assert_eq!(::run_network(&program, Some(2), &Topology::Pairs, false, config).unwrap().lines().last(),
+                   Some("process 0: gave up after 10 steps"));
+
+        // waiting for a value is not a loop, and sending or receiving one ends any loop
+        let program = parse_program("jgz p 3\nrcv a\njgz 1 -1\nsnd 1\njgz 1 -1").unwrap();
+        let config = Config { limits: vm::Limits { max_steps: Some(1000), detect_loops: true }, ..Config::default() };
+        let mut network = Network::with_config(&program, 2, &Topology::Pairs, config).unwrap();
+        assert_eq!(network.run(), Outcome::Stopped(1, Termination::Exhausted { steps: 1000 }));
+        assert_eq!(network.sends(), &[0, 500]);
+    }
+}
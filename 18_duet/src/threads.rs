@@ -0,0 +1,180 @@
+//! Runs each process of a network on its own thread. A process waiting for a
+//! value sleeps until one is sent to it, and the last process to block wakes
+//! every other one when none of them can go on.
+
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex};
+use std::thread;
+
+use {Instruction, Topology, new_process};
+use vm::State::*;
+use vm::{Config, Watchdog};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Running,
+    Waiting,
+    Halted,
+}
+
+/// Everything the threads share, behind one lock.
+struct Mailboxes {
+    queues : Vec<VecDeque<i64>>,
+    states : Vec<State>,
+    deadlock : bool,
+}
+
+impl Mailboxes {
+    /// True if every process has halted or is waiting with nothing to receive.
+    fn is_stuck(&self) -> bool {
+        self.states.iter().zip(&self.queues).all(|(&state, queue)| match state {
+            State::Running => false,
+            State::Waiting => queue.is_empty(),
+            State::Halted => true,
+        })
+    }
+}
+
+struct Shared {
+    mailboxes : Mutex<Mailboxes>,
+    /// Signalled when a value is sent, a process halts, or the network deadlocks.
+    changed : Condvar,
+}
+
+impl Shared {
+    /// Marks a process as stopped for good, waking the others in case that
+    /// leaves them deadlocked.
+    fn halt(&self, pid : usize) {
+        let mut mailboxes = self.mailboxes.lock().unwrap();
+        mailboxes.states[pid] = State::Halted;
+        mailboxes.deadlock = mailboxes.is_stuck();
+        self.changed.notify_all();
+    }
+}
+
+/// Runs one process until it halts, faults, runs out of steps, loops, or the
+/// network deadlocks, returning the number of values it sent if it halted or
+/// deadlocked.
+fn process_thread(pid : usize, program : &[Instruction], config : Config, targets : &[usize], shared : &Shared) -> Result<usize, String> {
+    let mut process = new_process(pid as i64, program, config);
+    let mut watchdog = Watchdog::for_process(&process);
+    let mut sent = 0;
+    loop {
+        if let Some(termination) = watchdog.check::<_, ()>(&process) {
+            shared.halt(pid);
+            return Err(format!("process {}: {}", pid, termination.into_result().unwrap_err()));
+        }
+        match process.step() {
+            Ran(_) => {},
+            Sending(x) => {
+                watchdog.io();
+                let mut mailboxes = shared.mailboxes.lock().unwrap();
+                for &to in targets {
+                    mailboxes.queues[to].push_back(x);
+                }
+                sent += 1;
+                shared.changed.notify_all();
+            },
+            Waiting(r) => {
+                let mut mailboxes = shared.mailboxes.lock().unwrap();
+                loop {
+                    if let Some(x) = mailboxes.queues[pid].pop_front() {
+                        mailboxes.states[pid] = State::Running;
+                        process.receive(r, x);
+                        watchdog.io();
+                        break;
+                    }
+                    if mailboxes.deadlock {
+                        return Ok(sent);
+                    }
+                    mailboxes.states[pid] = State::Waiting;
+                    if mailboxes.is_stuck() {
+                        mailboxes.deadlock = true;
+                        shared.changed.notify_all();
+                        return Ok(sent);
+                    }
+                    mailboxes = shared.changed.wait(mailboxes).unwrap();
+                }
+            },
+            Halted(_) => {
+                shared.halt(pid);
+                return Ok(sent);
+            },
+            Faulted(ip, fault) => {
+                shared.halt(pid);
+                return Err(format!("process {}: {} at line {}", pid, fault, ip + 1));
+            },
+        }
+    }
+}
+
+/// Runs `n` processes connected by `topology` on separate threads until every
+/// one has halted or deadlocked, like `Network::run`, returning the number of
+/// values each sent, or the errors of every process that couldn't go on.
+pub fn run_threads(program : &[Instruction], n : usize, topology : &Topology, config : Config) -> Result<Vec<usize>, String> {
+    let targets = topology.targets(n)?;
+    let shared = Shared {
+        mailboxes: Mutex::new(Mailboxes {
+            queues: vec![VecDeque::new(); n],
+            states: vec![State::Running; n],
+            deadlock: false,
+        }),
+        changed: Condvar::new(),
+    };
+
+    let results : Vec<Result<usize, String>> = thread::scope(|scope| {
+        let threads : Vec<_> = targets.iter().enumerate()
+            .map(|(pid, targets)| {
+                let shared = &shared;
+                scope.spawn(move || process_thread(pid, program, config, targets, shared))
+            })
+            .collect();
+        threads.into_iter().enumerate()
+            .map(|(pid, thread)| thread.join().unwrap_or_else(|_| Err(format!("process {} panicked", pid))))
+            .collect()
+    });
+
+    let errors : Vec<String> = results.iter().filter_map(|r| r.clone().err()).collect();
+    if errors.is_empty() {
+        Ok(results.into_iter().map(Result::unwrap).collect())
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use {Network, Outcome, parse_program};
+
+    #[test]
+    fn test_matches_network() {
+        let programs = [
+            // deadlocks
+            "set b 50\nsnd p\nadd b -1\njgz b -2\nrcv a\njgz 1 -1",
+            // halts after receiving one value
+            "snd p\nrcv a",
+            // some processes halt and the others deadlock
+            "snd p\njgz p 3\nrcv a\nrcv a",
+        ];
+        for input in &programs {
+            let program = parse_program(input).unwrap();
+            for &(n, ref topology) in &[(2, Topology::Pairs), (5, Topology::Ring), (4, Topology::Broadcast)] {
+                let mut network = Network::new(&program, n, topology).unwrap();
+                network.run();
+                assert_eq!(run_threads(&program, n, topology, Config::default()), Ok(network.sends().to_vec()), "{} on {:?}", input, topology);
+            }
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let program = parse_program("snd p\nrcv a").unwrap();
+        let mut network = Network::new(&program, 2, &Topology::Pairs).unwrap();
+        assert_eq!(network.run(), Outcome::Halted);
+        assert_eq!(run_threads(&program, 2, &Topology::Pairs, Config::default()), Ok(vec![1, 1]));
+
+        let program = parse_program("snd p\njgz p 3\nrcv a\nmod a 0").unwrap();
+        assert_eq!(run_threads(&program, 3, &Topology::Ring, Config::default()), Err(String::from("process 0: mod by zero at line 4")));
+    }
+}
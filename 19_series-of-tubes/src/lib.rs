@@ -0,0 +1,99 @@
+extern crate util;
+
+use util::{Grid, ParseError, Solution};
+
+fn parse_input(input : &str) -> Result<Grid<u8>, ParseError> {
+    Grid::parse(input, "path or letter", |c| {
+        if " |-+".contains(c) || c.is_ascii_uppercase() { Some(c as u8) } else { None }
+    })
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+use Direction::*;
+
+impl Direction {
+    fn apply(self, (x, y) : (isize, isize)) -> (isize, isize) {
+        match self {
+            Up    => (x, y - 1),
+            Down  => (x, y + 1),
+            Left  => (x - 1, y),
+            Right => (x + 1, y),
+        }
+    }
+}
+
+struct Path { path : String, steps : usize }
+
+fn follow_path(grid : &Grid<u8>) -> Path {
+    let start = grid.row(0).iter().position(|&c| c == b'|').unwrap_or(0);
+    let (mut x, mut y) = (start as isize, 0_isize);
+    let mut dir = Down;
+    let mut path = String::new();
+    let mut steps = 0;
+    loop {
+        match grid.get(x, y).cloned() {
+            None | Some(b' ') => return Path { path, steps },
+            Some(b'|') | Some(b'-') | Some(b'+') => { },
+            Some(c) => path.push(c as char)
+        }
+        let (mut xn, mut yn) = dir.apply((x, y));
+        match grid.get(xn, yn) {
+            None | Some(&b' ') => {
+                let dirs = match dir {
+                    Up   | Down  => [Left, Right],
+                    Left | Right => [Up,   Down],
+                };
+                for d in dirs.iter().cloned() {
+                    let (xd, yd) = d.apply((x, y));
+                    match grid.get(xd, yd) {
+                        None | Some(&b' ') => { },
+                        _ => { dir = d; xn = xd; yn = yd; break; }
+                    }
+                }
+            },
+            _ => {  }
+        }
+        steps += 1;
+        x = xn;
+        y = yn;
+    }
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const DAY : u32 = 19;
+
+    type Input = Grid<u8>;
+    type Answer1 = String;
+    type Answer2 = usize;
+    const LABEL1 : &'static str = "path";
+    const LABEL2 : &'static str = "steps";
+
+    fn parse(input : &str) -> Result<Grid<u8>, String> { Ok(parse_input(input)?) }
+
+    fn part1(grid : &Grid<u8>) -> Result<String, String> { Ok(follow_path(grid).path) }
+
+    fn part2(grid : &Grid<u8>) -> Result<usize, String> { Ok(follow_path(grid).steps) }
+}
+
+#[test]
+fn test_example() {
+    let input =
+"     |          
+     |  +--+    
+     A  |  C    
+ F---|----E|--+ 
+     |  |  |  D 
+     +B-+  +--+ ";
+    let grid = parse_input(input).unwrap();
+    let path = follow_path(&grid);
+    assert_eq!("ABCDEF", path.path);
+    assert_eq!(38, path.steps);
+}
@@ -0,0 +1,56 @@
+extern crate util;
+
+use util::{ParseError, Solution};
+
+fn check_consecutive(digits : &[u8]) -> Result<isize, String> {
+    let mut sum : isize = 0;
+    let iter = digits.iter().zip(digits.iter().cycle().skip(1));
+    for (&d1, &d2) in iter {
+        if ! d1.is_ascii_digit() {
+            return Err(format!("error: invalid digit '{}'", d1 as char));
+        }
+        if d1 == d2 {
+            sum += (d1 - b'0') as isize;
+        }
+    }
+    Ok(sum)
+}
+
+fn check_halfway_around(digits : &[u8]) -> Result<isize, String> {
+    let mut sum : isize = 0;
+    let size = digits.len();
+    for (i, &d1) in digits.iter().enumerate() {
+        if ! d1.is_ascii_digit() {
+            return Err(format!("error: invalid digit '{}'", d1 as char));
+        }
+        let d2 = digits[(i + size / 2) % size];
+        if d1 == d2 {
+            sum += (d1 - b'0') as isize;
+        }
+    }
+    Ok(sum)
+}
+
+fn parse_input(input : &str) -> Result<Vec<u8>, ParseError> {
+    let digits = input.trim();
+    match digits.find(|c : char| ! c.is_ascii_digit()) {
+        Some(i) => Err(ParseError::at(input, &digits[i..], "digit")),
+        None => Ok(digits.as_bytes().to_vec()),
+    }
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const DAY : u32 = 1;
+
+    type Input = Vec<u8>;
+    type Answer1 = isize;
+    type Answer2 = isize;
+
+    fn parse(input : &str) -> Result<Vec<u8>, String> { Ok(parse_input(input)?) }
+
+    fn part1(digits : &Vec<u8>) -> Result<isize, String> { check_consecutive(digits) }
+
+    fn part2(digits : &Vec<u8>) -> Result<isize, String> { check_halfway_around(digits) }
+}
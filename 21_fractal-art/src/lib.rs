@@ -0,0 +1,256 @@
+#[macro_use] extern crate nom;
+extern crate util;
+
+use util::{Grid, ParseError, Solution};
+use util::parse::parse_lines;
+
+macro_rules! parse_pat (
+    ($name : ident, $len : expr) => (
+        fn $name(input : &str) -> Result<[u8; $len * $len], ()> {
+            let mut pat = [0; $len * $len];
+            {
+                let mut iter = pat.iter_mut();
+                for c in input.chars().filter(|&c| c == '.' || c == '#') {
+                    match iter.next() {
+                        Some(v) => *v = c as u8,
+                        None => return Err(())
+                    }
+                }
+                if iter.next().is_some() {
+                    return Err(());
+                }
+            }
+            Ok(pat)
+        }
+    )
+);
+
+parse_pat!(parse_pat2, 2);
+parse_pat!(parse_pat3, 3);
+parse_pat!(parse_pat4, 4);
+
+named!(parse_rule<&str, (&str, &str)>, ws!(separated_pair!(is_a_s!("#./"), tag!("=>"), is_a_s!("#./"))));
+
+pub struct Rules(Vec<Rule<Pat2, Pat3>>, Vec<Rule<Pat3, Pat4>>);
+
+fn parse_input(input : &str) -> Result<Rules, ParseError> {
+    let mut rules2 = Vec::new();
+    let mut rules3 = Vec::new();
+    for (i, o) in parse_lines(input, parse_rule)? {
+        if let Ok(i) = parse_pat2(i) {
+            let o = parse_pat3(o).map_err(|_| ParseError::at(input, o, "3x3 pattern"))?;
+            rules2.push(Rule(patterns(&i), o));
+        } else if let Ok(i) = parse_pat3(i) {
+            let o = parse_pat4(o).map_err(|_| ParseError::at(input, o, "4x4 pattern"))?;
+            rules3.push(Rule(patterns(&i), o));
+        } else {
+            return Err(ParseError::at(input, i, "2x2 or 3x3 pattern"));
+        }
+    }
+    Ok(Rules(rules2, rules3))
+}
+
+type Pat2 = [u8; 4];
+type Pat3 = [u8; 9];
+type Pat4 = [u8; 16];
+
+trait PatternElem : Copy + Default + PartialEq + std::fmt::Debug { }
+impl PatternElem for u8 { }
+
+trait Pattern : Copy + Default + PartialEq + std::fmt::Debug { }
+impl<E : PatternElem> Pattern for [E; 4] { }
+impl<E : PatternElem> Pattern for [E; 9] { }
+impl<E : PatternElem> Pattern for [E; 16] { }
+
+trait SmallMatrix : Pattern {
+    fn rotate(&self) -> Self;
+    fn flip_v(&self) -> Self;
+    #[allow(dead_code)]
+    fn flip_h(&self) -> Self;
+}
+
+type PatSym<P> = [P; 8];
+
+#[derive(Debug)]
+struct Rule<P1 : SmallMatrix, P2 : Pattern>(PatSym<P1>, P2);
+
+impl<T : PatternElem> SmallMatrix for [T; 4] {
+    fn rotate(&self) -> Self {
+        [self[1], self[3], self[0], self[2]]
+    }
+    fn flip_v(&self) -> Self {
+        [self[2], self[3], self[0], self[1]]
+    }
+    fn flip_h(&self) -> Self {
+        [self[1], self[0], self[3], self[2]]
+    }
+}
+
+impl<T : PatternElem> SmallMatrix for [T; 9] {
+    fn rotate(&self) -> Self {
+        [self[2], self[5], self[8], self[1], self[4], self[7], self[0], self[3], self[6]]
+    }
+    fn flip_v(&self) -> Self {
+        [self[6], self[7], self[8], self[3], self[4], self[5], self[0], self[1], self[2]]
+    }
+    fn flip_h(&self) -> Self {
+        [self[2], self[1], self[0], self[5], self[4], self[3], self[8], self[7], self[6]]
+    }
+}
+
+fn patterns<M : SmallMatrix>(pat : &M) -> PatSym<M> {
+    let mut output = [M::default(); 8];
+    {
+        let mut iter = output.iter_mut();
+        let mut sym = *pat;
+        for _ in 0..4 {
+            *iter.next().unwrap() = sym;
+            *iter.next().unwrap() = sym.flip_v();
+            sym = sym.rotate();
+        }
+    }
+    output
+}
+
+fn match_rule<I : SmallMatrix, O : Pattern>(pat : &I, rules : &[Rule<I, O>]) -> Option<O> {
+    for Rule(i, o) in rules {
+        if i.iter().any(|r| pat == r) {
+            return Some(*o);
+        }
+    }
+    None
+}
+
+#[derive(Debug)]
+enum ApplyError {
+    InvalidSize,
+    BadPattern2(Pat2),
+    BadPattern3(Pat3),
+}
+
+impl std::fmt::Display for ApplyError {
+    fn fmt(&self, f : &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            ApplyError::InvalidSize => write!(f, "image size is not divisible by 2 or 3"),
+            ApplyError::BadPattern2(ref pat) => write!(f, "no rule matches pattern '{}'", String::from_utf8_lossy(pat)),
+            ApplyError::BadPattern3(ref pat) => write!(f, "no rule matches pattern '{}'", String::from_utf8_lossy(pat)),
+        }
+    }
+}
+
+fn apply_rules_2(input : &Grid<u8>, rules : &[Rule<Pat2, Pat3>]) -> Result<Grid<u8>, ApplyError> {
+    let len = input.width() / 2;
+    let mut output = Grid::new(len * 3, len * 3, b'.');
+    for i in 0..len {
+        for j in 0..len {
+            let (x, y) = (j * 2, i * 2);
+            let pat = [input[(x, y)], input[(x + 1, y)], input[(x, y + 1)], input[(x + 1, y + 1)]];
+            let pattern = match match_rule(&pat, rules) {
+                Some(pattern) => pattern,
+                None => return Err(ApplyError::BadPattern2(pat)),
+            };
+            for ii in 0..3 {
+                for jj in 0..3 {
+                    output[(j * 3 + jj, i * 3 + ii)] = pattern[ii * 3 + jj];
+                }
+            }
+        }
+    }
+    Ok(output)
+}
+
+fn apply_rules_3(input : &Grid<u8>, rules : &[Rule<Pat3, Pat4>]) -> Result<Grid<u8>, ApplyError> {
+    let len = input.width() / 3;
+    let mut output = Grid::new(len * 4, len * 4, b'.');
+    for i in 0..len {
+        for j in 0..len {
+            let mut pat = [0; 9];
+            for ii in 0..3 {
+                for jj in 0..3 {
+                    pat[ii * 3 + jj] = input[(j * 3 + jj, i * 3 + ii)];
+                }
+            }
+            let pattern = match match_rule(&pat, rules) {
+                Some(pattern) => pattern,
+                None => return Err(ApplyError::BadPattern3(pat)),
+            };
+            for ii in 0..4 {
+                for jj in 0..4 {
+                    output[(j * 4 + jj, i * 4 + ii)] = pattern[ii * 4 + jj];
+                }
+            }
+        }
+    }
+    Ok(output)
+}
+
+fn apply_rules(image : &Grid<u8>, patterns2 : &[Rule<Pat2, Pat3>], patterns3 : &[Rule<Pat3, Pat4>]) -> Result<Grid<u8>, ApplyError> {
+    if image.width().is_multiple_of(2) {
+        apply_rules_2(image, patterns2)
+    } else if image.width().is_multiple_of(3) {
+        apply_rules_3(image, patterns3)
+    } else {
+        Err(ApplyError::InvalidSize)
+    }
+}
+
+/// Applies the rules to the starting image, returning the final image.
+fn enhance(Rules(rules2, rules3) : &Rules, iterations : usize) -> Result<Grid<u8>, ApplyError> {
+    let mut image = Grid::from_vec(3, 3, b".#...####".to_vec());
+    for _ in 0..iterations {
+        image = apply_rules(&image, rules2, rules3)?;
+    }
+    Ok(image)
+}
+
+/// Prints the image after the given number of iterations (limited to 20).
+pub fn print_image(input : &str, iterations : usize) {
+    let rules = match parse_input(input) {
+        Ok(rules) => rules,
+        Err(e) => { eprintln!("{}", e); return; }
+    };
+    let iterations = if iterations <= 20 { iterations } else {
+        util::warn("large number of iterations, limiting to 20");
+        20
+    };
+    match enhance(&rules, iterations) {
+        Ok(image) => print!("{}", image),
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+fn pixels_on(rules : &Rules, iterations : usize) -> Result<usize, String> {
+    let image = enhance(rules, iterations).map_err(|e| e.to_string())?;
+    Ok(image.cells().iter().cloned().filter(|&c| c == b'#').count())
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const DAY : u32 = 21;
+
+    type Input = Rules;
+    type Answer1 = usize;
+    type Answer2 = usize;
+    const LABEL1 : &'static str = "Pixels on after 5 iterations";
+    const LABEL2 : &'static str = "Pixels on after 18 iterations";
+
+    fn parse(input : &str) -> Result<Rules, String> { Ok(parse_input(input)?) }
+
+    fn part1(rules : &Rules) -> Result<usize, String> { pixels_on(rules, 5) }
+
+    fn part2(rules : &Rules) -> Result<usize, String> { pixels_on(rules, 18) }
+}
+
+#[test]
+fn test_example() {
+    let input =
+"../.# => ##./#../...
+.#./..#/### => #..#/..../..../#..#";
+    let Rules(rules2, rules3) = parse_input(input).unwrap();
+    let image = Grid::from_vec(3, 3, ".#...####".as_bytes().to_vec());
+    let image = apply_rules(&image, &rules2, &rules3).unwrap();
+    assert_eq!(&b"#..#........#..#"[..], image.cells());
+    let image = apply_rules(&image, &rules2, &rules3).unwrap();
+    assert_eq!(&b"##.##.#..#........##.##.#..#........"[..], image.cells());
+}
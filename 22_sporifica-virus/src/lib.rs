@@ -0,0 +1,184 @@
+extern crate termion;
+extern crate util;
+
+use util::{Grid, ParseError, Progress, Solution};
+
+fn run_simulation<S, P>(mut grid : Grid<u8>, iterations : usize, mut progress : Progress, mut step : S, process : P) -> Result<usize, String>
+where S : FnMut(&mut u8, &mut isize, &mut isize, &mut usize),
+      P : Fn(&Grid<u8>) {
+          if grid.width() < 2 || grid.height() < 2 {
+              return Ok(0);
+          }
+
+          let mut infections = 0;
+          let mut index : (isize, isize) = (grid.width() as isize / 2, grid.height() as isize / 2);
+          let (mut x, mut y) : (isize, isize) = (0, -1);
+          for i in 0..iterations {
+              progress.update(i as u64).map_err(|t| t.with_partial(format!("{} infections", infections)))?;
+              {
+                  let node = unsafe { grid.get_unchecked_mut(index.0 as usize, index.1 as usize) };
+                  step(node, &mut x, &mut y, &mut infections);
+              }
+              index = (index.0 + x, index.1 + y);
+              if ! grid.contains(index.0, index.1) {
+                  let (width, height) = (grid.width() as isize, grid.height() as isize);
+                  let grow_w = width / 2;
+                  let grow_h = height / 2;
+                  let dc = if index.0 < width / 2 { grow_w } else { 0 };
+                  let dr = if index.1 < height / 2 { grow_h } else { 0 };
+                  grid.grow(dc as usize, (grow_w - dc) as usize, dr as usize, (grow_h - dr) as usize, b'.');
+                  index.0 += dc;
+                  index.1 += dr;
+              }
+              process(&grid);
+          }
+          Ok(infections)
+      }
+
+pub fn step(node : &mut u8, x : &mut isize, y : &mut isize, infections : &mut usize) {
+    if *node == b'#' {
+        *node = b'.';
+        *y *= -1;
+        std::mem::swap(x, y);
+    } else {
+        *infections += 1;
+        *node = b'#';
+        *x *= -1;
+        std::mem::swap(x, y);
+    }
+}
+
+pub fn step_evolved(node : &mut u8, x : &mut isize, y : &mut isize, infections : &mut usize) {
+    match *node {
+        b'.' => {
+            *node = b'W';
+            *x *= -1;
+            std::mem::swap(x, y);
+        },
+        b'#' => {
+            *node = b'F';
+            *y *= -1;
+            std::mem::swap(x, y);
+        },
+        b'W' => {
+            *infections += 1;
+            *node = b'#';
+        },
+        b'F' => {
+            *node = b'.';
+            *x *= -1;
+            *y *= -1;
+        },
+        _ => { }
+    }
+}
+
+fn simulate<S>(grid : Grid<u8>, iterations : usize, step : S) -> Result<usize, String>
+where S : FnMut(&mut u8, &mut isize, &mut isize, &mut usize) {
+    run_simulation(grid, iterations, Progress::new("bursting", Some(iterations as u64)), step, |_| { })
+}
+
+#[cfg(feature = "visualization")]
+fn print_grid(grid : &Grid<u8>, width : usize, height : usize) {
+    use std::io::prelude::*;
+    use termion::raw::IntoRawMode;
+
+    let stdout = std::io::stdout().into_raw_mode().unwrap();
+    let mut handle = stdout.lock();
+    write!(handle, "{}", termion::cursor::Goto(1, 2)).unwrap();
+    for row in grid.rows().take(height) {
+        handle.write_all(&row[..width]).unwrap();
+        write!(handle, "\r\n").unwrap();
+    }
+    std::thread::sleep(std::time::Duration::from_millis(33));
+}
+
+#[cfg(feature = "visualization")]
+pub fn simulate_print<S>(grid : Grid<u8>, iterations : usize, step : S) -> Result<usize, String>
+where S : FnMut(&mut u8, &mut isize, &mut isize, &mut usize) {
+    use termion::raw::IntoRawMode;
+    use termion::{clear, color, cursor};
+
+    // separate thread for capturing input (to exit cleanly)
+    let stdout = std::io::stdout().into_raw_mode().unwrap();
+    let input_thread = std::thread::spawn(move || {
+        use std::io::{Read, Write};
+        write!(stdout.lock(), "{hide}{clear_all}{top}{fg_color}{bg_color}press any key to exit{clear}{fg_reset}{bg_reset}",
+        hide = cursor::Hide,
+        clear_all = clear::All,
+        top = cursor::Goto(1, 1),
+        fg_color = color::Fg(color::White),
+        bg_color = color::Bg(color::Cyan),
+        clear = clear::UntilNewline,
+        fg_reset = color::Fg(color::Reset),
+        bg_reset = color::Bg(color::Reset)
+        ).unwrap();
+        std::io::stdin().bytes().next();
+        write!(stdout.lock(), "{}{}", cursor::Show, clear::All).unwrap();
+        stdout.lock().flush().unwrap();
+        std::mem::drop(stdout);
+        std::process::exit(0);
+    });
+    let (screen_w, screen_h) = termion::terminal_size().unwrap();
+    let result = run_simulation(grid, iterations, Progress::new("bursting", Some(iterations as u64)).hidden(), step, |grid|
+                                print_grid(grid, grid.width().min(screen_w as usize), grid.height().min(screen_h as usize - 2))
+                               );
+    input_thread.join().unwrap();
+    result
+}
+
+pub fn parse_input(input : &str) -> Result<Grid<u8>, ParseError> {
+    let grid = Grid::parse(input, "'.' or '#'", |c| if c == '.' || c == '#' { Some(c as u8) } else { None })?;
+    if grid.width() < 2 || grid.height() < 2 {
+        util::warn(format!("grid too small ({} x {})", grid.width(), grid.height()));
+    }
+    Ok(grid)
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const DAY : u32 = 22;
+
+    type Input = Grid<u8>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+    const LABEL1 : &'static str = "infections/original";
+    const LABEL2 : &'static str = "infections/evolved";
+
+    fn parse(input : &str) -> Result<Grid<u8>, String> { Ok(parse_input(input)?) }
+
+    fn part1(grid : &Grid<u8>) -> Result<usize, String> { simulate(grid.clone(), 10000, step) }
+
+    fn part2(grid : &Grid<u8>) -> Result<usize, String> { simulate(grid.clone(), 10_000_000, step_evolved) }
+}
+
+#[test]
+fn test_part_one() {
+    let input =
+        *b".........\
+.........\
+.........\
+.....#...\
+...#.....\
+.........\
+.........\
+.........";
+    let grid = Grid::from_vec(9, 8, input.to_vec());
+    assert_eq!(Ok(5587), simulate(grid, 10000, step));
+}
+
+#[test]
+fn test_part_two() {
+    let input =
+        *b".........\
+.........\
+.........\
+.....#...\
+...#.....\
+.........\
+.........\
+.........";
+    let grid = Grid::from_vec(9, 8, input.to_vec());
+    assert_eq!(Ok(2511944), simulate(grid, 10_000_000, step_evolved));
+}
@@ -0,0 +1,80 @@
+//! Compares the interpreter with the program compiled to closures on the part
+//! two brute force, both with and without the peephole optimizer:
+//!
+//!     cargo run --release -p coprocessor-conflation --example compile_bench [SCALE]
+//!
+//! Without the optimizer, the puzzle's numbers are scaled down from 100 times
+//! the first value of `b` to SCALE (1 by default) times, so that it finishes.
+
+extern crate coprocessor_conflation;
+extern crate vm;
+
+use std::time::{Duration, Instant};
+
+use coprocessor_conflation::{Instruction, optimize, parse_program, run_compiled, run_interpreted};
+use vm::Config;
+
+/// The program of README.md, with `{}` for the multiplier of `b`.
+const PROGRAM : &str = "\
+set b 67
+set c b
+jnz a 2
+jnz 1 5
+mul b {}
+sub b -100000
+set c b
+sub c -17000
+set f 1
+set d 2
+set e 2
+set g d
+mul g e
+sub g b
+jnz g 2
+set f 0
+sub e -1
+set g e
+sub g b
+jnz g -8
+sub d -1
+set g d
+sub g b
+jnz g -13
+jnz f 2
+sub h -1
+set g b
+sub g c
+jnz g 2
+jnz 1 3
+sub b -17
+jnz 1 -23";
+
+fn program(scale : i64) -> Vec<Instruction> {
+    // scale the offsets added to b and c along with the multiplier
+    let input = PROGRAM.replace("{}", &scale.to_string())
+        .replace("-100000", &(-1000 * scale).to_string())
+        .replace("-17000", &(-170 * scale).to_string());
+    parse_program(&input).unwrap()
+}
+
+fn time<F : Fn() -> i64>(run : F) -> (i64, Duration) {
+    let start = Instant::now();
+    let h = run();
+    (h, start.elapsed())
+}
+
+fn millis(duration : Duration) -> f64 { duration.as_secs_f64() * 1000.0 }
+
+fn compare(label : &str, program : &[Instruction]) {
+    let (h, interpreted) = time(|| run_interpreted(program, Config::default()).unwrap().into_result().unwrap());
+    let (h2, compiled) = time(|| run_compiled(program, Config::default()).unwrap().into_result().unwrap());
+    assert_eq!(h, h2);
+    println!("{:<24} h = {:<6} interpreted {:>10.3}ms  compiled {:>10.3}ms  speedup {:.2}x",
+             label, h, millis(interpreted), millis(compiled), millis(interpreted) / millis(compiled));
+}
+
+fn main() {
+    let scale = std::env::args().nth(1).map_or(1, |scale| scale.parse().expect("invalid scale"));
+    compare(&format!("unoptimized, scale {}", scale), &program(scale));
+    compare("optimized, scale 100", &optimize(&program(100)));
+}
@@ -0,0 +1,416 @@
+//! Turns a coprocessor program back into structured pseudo-Rust.
+//!
+//! The program is split into a control-flow graph of basic blocks, and every
+//! edge jumping back to an earlier block is taken as the end of a loop. The
+//! instructions are then nested into `loop`s at those back edges and `if`s at
+//! forward jumps. Jumps that don't fit that structure are kept as `goto`s, and
+//! jumps by a register are flagged as indirect since their target can't be
+//! known before running the program.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use {Instruction, Register, Value};
+use Instruction::*;
+use Value::*;
+
+/// Where control can go after a basic block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    /// To the block starting at the instruction.
+    Block(usize),
+    /// Outside the program, which halts it.
+    Exit,
+    /// By the value of a register, to an unknown block.
+    Indirect,
+}
+
+/// A run of instructions with no jumps into or out of its middle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    /// Index of the first instruction.
+    pub start : usize,
+    /// Index past the last instruction.
+    pub end : usize,
+    pub successors : Vec<Edge>,
+}
+
+/// The control-flow graph of a program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cfg {
+    pub blocks : Vec<Block>,
+}
+
+/// Where a jump by a constant offset lands. Offsets too large for the target to
+/// fit in 64 bits go past the end of the program, like any other large offset.
+fn target(program : &[Instruction], from : usize, offset : i64) -> Edge {
+    let to = (from as i64).saturating_add(offset);
+    if to < 0 || to >= program.len() as i64 { Edge::Exit } else { Edge::Block(to as usize) }
+}
+
+/// True if the value is a nonzero number, i.e. a jump on it is always taken.
+fn always(v : Value) -> bool { matches!(v, Num(n) if n != 0) }
+
+impl Cfg {
+    pub fn new(program : &[Instruction]) -> Cfg {
+        let mut leaders = vec![false; program.len() + 1];
+        leaders[0] = true;
+        for (i, &instruction) in program.iter().enumerate() {
+            if let Jnz(_, offset) = instruction {
+                leaders[i + 1] = true;
+                if let Num(offset) = offset {
+                    if let Edge::Block(to) = target(program, i, offset) {
+                        leaders[to] = true;
+                    }
+                }
+            }
+        }
+
+        let starts : Vec<usize> = (0..program.len()).filter(|&i| leaders[i]).collect();
+        let blocks = starts.iter().enumerate().map(|(b, &start)| {
+            let end = starts.get(b + 1).cloned().unwrap_or(program.len());
+            let fall_through = if end < program.len() { Edge::Block(end) } else { Edge::Exit };
+            let successors = match program[end - 1] {
+                Jnz(Num(0), _) => vec![fall_through],
+                Jnz(c, Num(offset)) if always(c) => vec![target(program, end - 1, offset)],
+                Jnz(_, Num(offset)) => vec![fall_through, target(program, end - 1, offset)],
+                Jnz(c, Reg(_)) if always(c) => vec![Edge::Indirect],
+                Jnz(_, Reg(_)) => vec![fall_through, Edge::Indirect],
+                _ => vec![fall_through],
+            };
+            Block { start, end, successors }
+        }).collect();
+        Cfg { blocks }
+    }
+
+    /// Edges to the same or an earlier block, as `(jump, target)` instruction
+    /// indices.
+    pub fn back_edges(&self) -> Vec<(usize, usize)> {
+        self.blocks.iter().flat_map(|block| block.successors.iter().filter_map(move |&edge| match edge {
+            Edge::Block(to) if to <= block.start => Some((block.end - 1, to)),
+            _ => None,
+        })).collect()
+    }
+
+    /// Indices of the jumps by a register.
+    pub fn indirect_jumps(&self) -> Vec<usize> {
+        self.blocks.iter()
+            .filter(|block| block.successors.contains(&Edge::Indirect))
+            .map(|block| block.end - 1)
+            .collect()
+    }
+}
+
+/// A jump condition: the value is nonzero, or zero if negated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Cond {
+    value : Value,
+    nonzero : bool,
+}
+
+impl Cond {
+    fn not(self) -> Cond { Cond { nonzero: ! self.nonzero, ..self } }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Stmt {
+    Op(Instruction),
+    If(Cond, Vec<Stmt>, Vec<Stmt>),
+    Loop(Vec<Stmt>),
+    While(Cond, Vec<Stmt>),
+    Break,
+    Continue,
+    Halt,
+    /// A jump that couldn't be structured, to the instruction index.
+    Goto(usize),
+    /// A jump by the register at the instruction index.
+    Indirect(usize, Register),
+}
+
+/// The loop being structured.
+#[derive(Clone, Copy)]
+struct Loop {
+    head : usize,
+    exit : usize,
+}
+
+struct Structurer<'a> {
+    program : &'a [Instruction],
+    /// Loop headers with the index of the last jump back to them.
+    loops : BTreeMap<usize, usize>,
+}
+
+impl<'a> Structurer<'a> {
+    fn new(program : &'a [Instruction]) -> Structurer<'a> {
+        let mut loops = BTreeMap::new();
+        for (jump, head) in Cfg::new(program).back_edges() {
+            let latch = loops.entry(head).or_insert(jump);
+            *latch = jump.max(*latch);
+        }
+        Structurer { program, loops }
+    }
+
+    /// True if the statements only leave the loop ending at `exit`.
+    fn exits(&self, stmts : &[Stmt], exit : usize) -> bool {
+        match *stmts {
+            [Stmt::Break] => true,
+            [Stmt::Halt] => exit == self.program.len(),
+            _ => false,
+        }
+    }
+
+    /// An unconditional jump from within a block.
+    fn jump(&self, to : Edge, current : Option<Loop>) -> Vec<Stmt> {
+        match (to, current) {
+            (Edge::Exit, _) => vec![Stmt::Halt],
+            (Edge::Block(to), Some(l)) if to == l.exit => vec![Stmt::Break],
+            (Edge::Block(to), Some(l)) if to == l.head => vec![Stmt::Continue],
+            (Edge::Block(to), _) => vec![Stmt::Goto(to)],
+            (Edge::Indirect, _) => unreachable!(),
+        }
+    }
+
+    /// Structures the instructions from `lo` up to `hi`, where control goes
+    /// on after the last one.
+    fn block(&self, lo : usize, hi : usize, current : Option<Loop>) -> Vec<Stmt> {
+        let mut stmts = Vec::new();
+        let mut i = lo;
+        while i < hi {
+            let in_body = current.is_some_and(|l| l.head == i && i == lo);
+            if let Some(&latch) = self.loops.get(&i).filter(|&&latch| latch < hi && ! in_body) {
+                let this = Some(Loop { head: i, exit: latch + 1 });
+                let mut body = self.block(i, latch, this);
+                stmts.push(match self.program[latch] {
+                    Jnz(c, _) if always(c) => match body.first().cloned() {
+                        Some(Stmt::If(cond, ref then, ref otherwise)) if otherwise.is_empty() && self.exits(then, latch + 1) => {
+                            body.remove(0);
+                            Stmt::While(cond.not(), body)
+                        },
+                        _ => Stmt::Loop(body),
+                    },
+                    Jnz(c, _) => {
+                        body.push(Stmt::If(Cond { value: c, nonzero: false }, vec![Stmt::Break], vec![]));
+                        Stmt::Loop(body)
+                    },
+                    _ => unreachable!(),
+                });
+                i = latch + 1;
+                continue;
+            }
+
+            match self.program[i] {
+                Jnz(Num(0), _) => { },
+                Jnz(c, Reg(r)) if always(c) => stmts.push(Stmt::Indirect(i, r)),
+                Jnz(c, Reg(r)) => stmts.push(Stmt::If(Cond { value: c, nonzero: true }, vec![Stmt::Indirect(i, r)], vec![])),
+                Jnz(c, Num(offset)) if always(c) => if offset != 1 {
+                    stmts.extend(self.jump(target(self.program, i, offset), current));
+                },
+                Jnz(c, Num(offset)) => {
+                    let cond = Cond { value: c, nonzero: true };
+                    let to = (i as i64).saturating_add(offset);
+                    if to > i as i64 + 1 && to <= hi as i64 {
+                        let to = to as usize;
+                        let skip = match self.program[to - 1] {
+                            Jnz(c, Num(offset)) if always(c) && to - 1 > i => Some((to as i64 - 1).saturating_add(offset)),
+                            _ => None,
+                        };
+                        match skip {
+                            Some(end) if end > to as i64 && end <= hi as i64 => {
+                                let end = end as usize;
+                                let (then, otherwise) = (self.block(i + 1, to - 1, current), self.block(to, end, current));
+                                stmts.push(if then.is_empty() { Stmt::If(cond, otherwise, then) } else { Stmt::If(cond.not(), then, otherwise) });
+                                i = end;
+                            },
+                            _ => {
+                                stmts.push(Stmt::If(cond.not(), self.block(i + 1, to, current), vec![]));
+                                i = to;
+                            },
+                        }
+                        continue;
+                    }
+                    stmts.push(Stmt::If(cond, self.jump(target(self.program, i, offset), current), vec![]));
+                },
+                instruction => stmts.push(Stmt::Op(instruction)),
+            }
+            i += 1;
+        }
+        stmts
+    }
+}
+
+fn write_cond(out : &mut String, cond : Cond) {
+    write!(out, "{} {} 0", cond.value, if cond.nonzero { "!=" } else { "==" }).unwrap();
+}
+
+fn write_block(out : &mut String, stmts : &[Stmt], depth : usize) {
+    for stmt in stmts {
+        write_stmt(out, stmt, depth);
+    }
+}
+
+fn write_stmt(out : &mut String, stmt : &Stmt, depth : usize) {
+    let indent = "    ".repeat(depth);
+    out.push_str(&indent);
+    match *stmt {
+        Stmt::Op(Set(x, y)) => writeln!(out, "{} = {};", x, y),
+        Stmt::Op(Sub(x, Num(y))) if y < 0 => writeln!(out, "{} += {};", x, -y),
+        Stmt::Op(Sub(x, y)) => writeln!(out, "{} -= {};", x, y),
+        Stmt::Op(Mul(x, y)) => writeln!(out, "{} *= {};", x, y),
+        Stmt::Op(instruction) => writeln!(out, "{};", instruction),
+        Stmt::If(cond, ref then, ref otherwise) => {
+            out.push_str("if ");
+            write_cond(out, cond);
+            out.push_str(" {\n");
+            write_block(out, then, depth + 1);
+            if ! otherwise.is_empty() {
+                writeln!(out, "{}}} else {{", indent).unwrap();
+                write_block(out, otherwise, depth + 1);
+            }
+            writeln!(out, "{}}}", indent)
+        },
+        Stmt::Loop(ref body) => {
+            out.push_str("loop {\n");
+            write_block(out, body, depth + 1);
+            writeln!(out, "{}}}", indent)
+        },
+        Stmt::While(cond, ref body) => {
+            out.push_str("while ");
+            write_cond(out, cond);
+            out.push_str(" {\n");
+            write_block(out, body, depth + 1);
+            writeln!(out, "{}}}", indent)
+        },
+        Stmt::Break => writeln!(out, "break;"),
+        Stmt::Continue => writeln!(out, "continue;"),
+        Stmt::Halt => writeln!(out, "return;"),
+        Stmt::Goto(to) => writeln!(out, "goto line {}; // unstructured jump", to + 1),
+        Stmt::Indirect(at, r) => writeln!(out, "goto line {} + {}; // indirect jump", at + 1, r),
+    }.unwrap();
+}
+
+/// Decompiles the program into pseudo-Rust.
+pub fn decompile(program : &[Instruction]) -> String {
+    let mut out = String::new();
+    write_block(&mut out, &Structurer::new(program).block(0, program.len(), None), 0);
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parse_program;
+
+    #[test]
+    fn test_puzzle() {
+        let program = parse_program("\
+set b 67
+set c b
+jnz a 2
+jnz 1 5
+mul b 100
+sub b -100000
+set c b
+sub c -17000
+set f 1
+set d 2
+set e 2
+set g d
+mul g e
+sub g b
+jnz g 2
+set f 0
+sub e -1
+set g e
+sub g b
+jnz g -8
+sub d -1
+set g d
+sub g b
+jnz g -13
+jnz f 2
+sub h -1
+set g b
+sub g c
+jnz g 2
+jnz 1 3
+sub b -17
+jnz 1 -23").unwrap();
+        assert_eq!(Cfg::new(&program).back_edges(), vec![(19, 11), (23, 10), (31, 8)]);
+        assert_eq!(decompile(&program), "\
+b = 67;
+c = b;
+if a != 0 {
+    b *= 100;
+    b += 100000;
+    c = b;
+    c += 17000;
+}
+loop {
+    f = 1;
+    d = 2;
+    loop {
+        e = 2;
+        loop {
+            g = d;
+            g *= e;
+            g -= b;
+            if g == 0 {
+                f = 0;
+            }
+            e += 1;
+            g = e;
+            g -= b;
+            if g == 0 {
+                break;
+            }
+        }
+        d += 1;
+        g = d;
+        g -= b;
+        if g == 0 {
+            break;
+        }
+    }
+    if f == 0 {
+        h += 1;
+    }
+    g = b;
+    g -= c;
+    if g == 0 {
+        return;
+    }
+    b += 17;
+}
+");
+    }
+
+    #[test]
+    fn test_while() {
+        let program = parse_program("set a 3\njnz a 2\njnz 1 3\nsub a 1\njnz 1 -3").unwrap();
+        assert_eq!(decompile(&program), "\
+a = 3;
+while a != 0 {
+    a -= 1;
+}
+");
+    }
+
+    #[test]
+    fn test_jumps() {
+        let program = parse_program("jnz a 2\njnz 1 3\nset b 1\nsub a 1\njnz b c\njnz a -3\nset b 2").unwrap();
+        assert_eq!(Cfg::new(&program).indirect_jumps(), vec![4]);
+        assert_eq!(decompile(&program), "\
+if a != 0 {
+    b = 1;
+    a -= 1;
+}
+if b != 0 {
+    goto line 5 + c; // indirect jump
+}
+if a != 0 {
+    goto line 3; // unstructured jump
+}
+b = 2;
+");
+    }
+}
@@ -0,0 +1,302 @@
+extern crate util;
+extern crate vm;
+
+pub mod decompile;
+mod optimize;
+
+pub use decompile::decompile;
+pub use optimize::optimize;
+use std::fmt;
+
+use util::{ParseError, Progress, Solution};
+use vm::{Compile, Compiled, Config, Effect, Encode, InstructionSet, Operands, Program, Registers, Runner, Termination, Watchdog};
+use vm::arith::{Arithmetic, BinOp};
+use vm::bytecode::Reader;
+use vm::compile::{self, Op, Stop};
+
+pub use vm::{Register, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Instruction {
+    Set(Register, Value),
+    Sub(Register, Value),
+    Mul(Register, Value),
+    Jnz(Value, Value),
+    /// Counts `e` up to `b` (see `optimize`).
+    CountLoop { e : Register, b : Value, g : Register },
+    /// Counts `e` up to `b`, clearing `f` if `d * e == b` along the way.
+    DivisorLoop { d : Value, e : Register, b : Value, f : Register, g : Register },
+    /// Counts `d` up to `b` and, for each `d`, `e` from `e0` up to `b`,
+    /// clearing `f` if `d * e == b` along the way.
+    CompositeLoop { d : Register, e0 : Value, e : Register, b : Value, f : Register, g : Register },
+}
+use Instruction::*;
+
+impl InstructionSet for Instruction {
+    const REGISTERS : usize = 8;
+
+    fn decode(args : &Operands) -> Result<Instruction, ParseError> {
+        Ok(match args.mnemonic() {
+            "set" => Set(args.register(0)?, args.value(1)?),
+            "sub" => Sub(args.register(0)?, args.value(1)?),
+            "mul" => Mul(args.register(0)?, args.value(1)?),
+            "jnz" => Jnz(args.value(0)?, args.offset(1)?),
+            // fused instructions only make sense in front of the loop they
+            // replace, so they come from the optimizer or bytecode, never text
+            _ => return Err(args.unknown()),
+        })
+    }
+
+    fn execute(&self, registers : &mut Registers) -> Effect {
+        match *self {
+            Set(x, y) => {
+                let y = registers.get(y);
+                registers.set(x, y);
+            },
+            Sub(x, y) => return registers.sub(x, y),
+            Mul(x, y) => return registers.mul(x, y),
+            Jnz(x, y) => if registers.get(x) != 0 {
+                return Effect::Jump(registers.get(y));
+            },
+            CountLoop { .. } | DivisorLoop { .. } | CompositeLoop { .. } => return optimize::execute(*self, registers),
+        }
+        Effect::Next
+    }
+}
+
+/// Fused instructions are printed as `count`, `divisors` and `composites`
+/// with their fields in order, so that optimized programs can be read. They
+/// can't be parsed back (see `decode`); save them as bytecode instead.
+impl fmt::Display for Instruction {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Set(x, y) => write!(f, "set {} {}", x, y),
+            Sub(x, y) => write!(f, "sub {} {}", x, y),
+            Mul(x, y) => write!(f, "mul {} {}", x, y),
+            Jnz(x, y) => write!(f, "jnz {} {}", x, y),
+            CountLoop { e, b, g } => write!(f, "count {} {} {}", e, b, g),
+            DivisorLoop { d, e, b, f: r, g } => write!(f, "divisors {} {} {} {} {}", d, e, b, r, g),
+            CompositeLoop { d, e0, e, b, f: r, g } => write!(f, "composites {} {} {} {} {} {}", d, e0, e, b, r, g),
+        }
+    }
+}
+
+impl Encode for Instruction {
+    const NAME : &'static str = "coprocessor";
+
+    fn encode(&self) -> (u8, Vec<Value>) {
+        use Value::Reg;
+        match *self {
+            Set(x, y) => (0, vec![Reg(x), y]),
+            Sub(x, y) => (1, vec![Reg(x), y]),
+            Mul(x, y) => (2, vec![Reg(x), y]),
+            Jnz(x, y) => (3, vec![x, y]),
+            CountLoop { e, b, g } => (4, vec![Reg(e), b, Reg(g)]),
+            DivisorLoop { d, e, b, f, g } => (5, vec![d, Reg(e), b, Reg(f), Reg(g)]),
+            CompositeLoop { d, e0, e, b, f, g } => (6, vec![Reg(d), e0, Reg(e), b, Reg(f), Reg(g)]),
+        }
+    }
+
+    fn decode_bytes(opcode : u8, args : &mut Reader) -> Result<Instruction, String> {
+        Ok(match opcode {
+            0 => Set(args.register()?, args.value()?),
+            1 => Sub(args.register()?, args.value()?),
+            2 => Mul(args.register()?, args.value()?),
+            3 => Jnz(args.value()?, args.value()?),
+            4 => CountLoop { e: args.register()?, b: args.value()?, g: args.register()? },
+            5 => DivisorLoop { d: args.value()?, e: args.register()?, b: args.value()?, f: args.register()?, g: args.register()? },
+            6 => CompositeLoop { d: args.register()?, e0: args.value()?, e: args.register()?, b: args.value()?, f: args.register()?, g: args.register()? },
+            _ => return Err(args.unknown()),
+        })
+    }
+}
+
+impl Compile for Instruction {
+    fn compile(&self, ip : i64, mode : Arithmetic) -> Op {
+        match *self {
+            Set(x, y) => compile::set(x, y),
+            Sub(x, y) => compile::binary(x, y, BinOp::Sub, mode),
+            Mul(x, y) => compile::binary(x, y, BinOp::Mul, mode),
+            Jnz(x, y) => compile::jump_if(x, |x| x != 0, y, ip),
+            CountLoop { .. } | DivisorLoop { .. } | CompositeLoop { .. } => compile::interpret(*self, ip),
+        }
+    }
+}
+
+pub fn parse_program(input : &str) -> Result<Vec<Instruction>, ParseError> {
+    vm::parse_program(input)
+}
+
+type Process<'a> = vm::Process<'a, Instruction>;
+use vm::State::*;
+
+fn new_process(flag : i64, program : &[Instruction], config : Config) -> Process<'_> {
+    let mut process = Process::with_config(program, config);
+    process.set_register('a', flag);
+    process
+}
+
+pub fn run_mul_count(program : &[Instruction], config : Config) -> Termination<u64> {
+    let mut process = new_process(0, program, config);
+    process.enable_profile();
+    let mut watchdog = Watchdog::for_process(&process);
+    loop {
+        if let Some(termination) = watchdog.check(&process) {
+            return termination;
+        }
+        match process.step() {
+            Halted(_) => break,
+            Faulted(ip, fault) => return Termination::Faulted { ip, fault },
+            _ => {},
+        }
+    }
+    let hits = process.profile().unwrap().hits();
+    Termination::Halted(program.iter().zip(hits)
+        .filter(|&(instruction, _)| matches!(*instruction, Mul(_, _)))
+        .map(|(_, &hits)| hits)
+        .sum())
+}
+
+/// Runs the program for at most `max_steps` steps, with register `a` set as
+/// in part two if `flag` is true, and reports how often each line ran (see
+/// `vm::trace::profile`).
+pub fn profile(program : &[Instruction], flag : bool, max_steps : Option<u64>, trace_file : Option<&str>, config : Config) -> Result<String, String> {
+    vm::trace::profile(&mut new_process(flag as i64, program, config), max_steps, trace_file)
+}
+
+/// Runs the program under the debugger, with register `a` set as in part two
+/// if `flag` is true.
+pub fn debug(program : &[Instruction], flag : bool, config : Config) {
+    vm::debug::debug(new_process(flag as i64, program, config));
+}
+
+/// Runs part two on the optimized program (see `optimize`), compiled to
+/// closures (see `vm::compile`) unless loops are to be detected.
+pub fn bruteforce(program : &[Instruction], config : Config) -> Result<Termination<i64>, String> {
+    let program = optimize(program);
+    if config.limits.detect_loops {
+        run_interpreted(&program, config)
+    } else {
+        run_compiled(&program, config)
+    }
+}
+
+/// Runs the program with register `a` set to 1 on the interpreter.
+pub fn run_interpreted(program : &[Instruction], config : Config) -> Result<Termination<i64>, String> {
+    let mut progress = Progress::new("brute force", None);
+    let mut process = new_process(1, program, config);
+    let mut watchdog = Watchdog::for_process(&process);
+    loop {
+        progress.update(watchdog.steps()).map_err(|t| t.with_partial(format!("h = {}", process.get_register('h'))))?;
+        if let Some(termination) = watchdog.check(&process) {
+            return Ok(termination);
+        }
+        match process.step() {
+            Halted(_) => return Ok(Termination::Halted(process.get_register('h'))),
+            Faulted(ip, fault) => return Ok(Termination::Faulted { ip, fault }),
+            _ => {},
+        }
+    }
+}
+
+/// Number of steps the compiled program runs between checks for a timeout.
+const CHUNK : u64 = 1 << 16;
+
+/// Runs the program with register `a` set to 1, compiled to closures, for at
+/// most the config's `max_steps` steps.
+pub fn run_compiled(program : &[Instruction], config : Config) -> Result<Termination<i64>, String> {
+    let mut progress = Progress::new("brute force", None);
+    let max_steps = config.limits.max_steps;
+    let code = Compiled::with_mode(program, config.arithmetic);
+    let mut runner = Runner::new(&code);
+    runner.set_register('a', 1);
+    loop {
+        progress.update(runner.steps()).map_err(|t| t.with_partial(format!("h = {}", runner.get_register('h'))))?;
+        let chunk = max_steps.map_or(CHUNK, |max| u64::min(CHUNK, max - runner.steps()));
+        if chunk == 0 {
+            return Ok(Termination::Exhausted { steps: runner.steps() });
+        }
+        match runner.run(chunk) {
+            Stop::Halted(_) => return Ok(Termination::Halted(runner.get_register('h'))),
+            Stop::Faulted(ip, fault) => return Ok(Termination::Faulted { ip, fault }),
+            _ => {},
+        }
+    }
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const DAY : u32 = 23;
+
+    type Input = Program<Instruction>;
+    type Answer1 = u64;
+    type Answer2 = i64;
+    const LABEL1 : &'static str = "debug multiplications";
+    const LABEL2 : &'static str = "value of h";
+
+    fn parse(input : &str) -> Result<Program<Instruction>, String> { Ok(Program::new(parse_program(input)?)) }
+
+    fn part1(program : &Program<Instruction>) -> Result<u64, String> {
+        run_mul_count(&program.instructions, program.config).into_result()
+    }
+
+    /// Brute force, with the inner loops optimized away (see README.md for solving by hand).
+    fn part2(program : &Program<Instruction>) -> Result<i64, String> {
+        bruteforce(&program.instructions, program.config)?.into_result()
+    }
+}
+
+#[test]
+fn test_mul_count() {
+    let program = parse_program("set b 3\nset c b\nmul c b\nsub b 1\njnz b -2").unwrap();
+    assert_eq!(run_mul_count(&program, Config::default()), Termination::Halted(3));
+}
+
+#[test]
+fn test_compiled() {
+    let program = optimize(&parse_program("set b 30\nset f 1\nset d 2\nset e 2\nset g d\nmul g e\nsub g b\njnz g 2\nset f 0\n\
+                                             sub e -1\nset g e\nsub g b\njnz g -8\nsub d -1\nset g d\nsub g b\njnz g -13\n\
+                                             jnz f 2\nsub h -1\nsub b -1\nset g b\nsub g 40\njnz g -21").unwrap());
+    assert_eq!(run_compiled(&program, Config::default()), run_interpreted(&program, Config::default()));
+    assert_eq!(run_compiled(&program, Config::default()), Ok(Termination::Halted(8)));
+    let limited = Config { limits: vm::Limits { max_steps: Some(10), detect_loops: false }, ..Config::default() };
+    assert_eq!(run_compiled(&program, limited), Ok(Termination::Exhausted { steps: 10 }));
+}
+
+#[test]
+fn test_jump_overflow() {
+    let program = parse_program("set a 1\njnz 1 9223372036854775807").unwrap();
+    let fault = vm::Fault::JumpOverflow;
+    assert_eq!(run_mul_count(&program, Config::default()), Termination::Faulted { ip: 1, fault });
+    assert_eq!(run_interpreted(&program, Config::default()), Ok(Termination::Faulted { ip: 1, fault }));
+    assert_eq!(run_compiled(&program, Config::default()), Ok(Termination::Faulted { ip: 1, fault }));
+    assert_eq!(decompile(&program), "a = 1;\nreturn;\n");
+}
+
+#[test]
+fn test_round_trip() {
+    let input = "set b 67\nsub b -100000\nmul g e\njnz 1 -23\n";
+    let program = parse_program(input).unwrap();
+    assert_eq!(vm::disassemble(&program), input);
+    assert_eq!(parse_program(&vm::disassemble(&program)), Ok(program.clone()));
+    assert_eq!(vm::bytecode::from_bytes(&vm::bytecode::to_bytes(&program)), Ok(program.clone()));
+
+    let program = optimize(&parse_program("set g d\nmul g e\nsub g b\njnz g 2\nset f 0\nsub e -1\nset g e\nsub g b\njnz g -8").unwrap());
+    assert_eq!(program[0], DivisorLoop { d: Value::Reg('d'), e: 'e', b: Value::Reg('b'), f: 'f', g: 'g' });
+    assert_eq!(vm::disassemble(&program[..1]), "divisors d e b f g\n");
+    assert_eq!(vm::bytecode::from_bytes(&vm::bytecode::to_bytes(&program)), Ok(program.clone()));
+}
+
+#[test]
+fn test_parse_error() {
+    let e = parse_program("set b 67\nset c b\nset i 1\nmul b c\n").unwrap_err();
+    assert_eq!((e.line, e.column, e.snippet.as_str(), e.expected.as_str()), (3, 5, "set i 1", "register"));
+    let e = parse_program("set b 67\njnz h i\n").unwrap_err();
+    assert_eq!((e.line, e.column, e.expected.as_str()), (2, 7, "register or number"));
+    let e = parse_program("set b 67\nadd b 1\nmul b c\n").unwrap_err();
+    assert_eq!((e.line, e.column, e.expected.as_str()), (2, 1, "known keyword"));
+    let e = parse_program("set e 1\ncount a 1 a\n").unwrap_err();
+    assert_eq!((e.line, e.column, e.expected.as_str()), (2, 1, "known keyword"));
+    assert!(Puzzle::parse("set a 1\nset z 2").unwrap_err().starts_with("line 2, column 5: expected register\n"));
+}
@@ -1,191 +1,10 @@
+extern crate coprocessor_conflation;
 extern crate util;
-#[macro_use] extern crate nom;
 extern crate clap;
+extern crate vm;
 
-pub type Register = char;
-
-#[derive(Debug, Clone, Copy)]
-pub enum Value {
-    Reg(Register),
-    Num(i64)
-}
-use Value::*;
-
-#[derive(Debug, Clone, Copy)]
-pub enum Instruction {
-    Set(Register, Value),
-    Sub(Register, Value),
-    Mul(Register, Value),
-    Jnz(Value, Value),
-}
-use Instruction::*;
-
-mod parse {
-    use super::{Register, Value, Instruction};
-    use Value::*;
-    use Instruction::*;
-    use nom;
-
-    named!(num<&str, i64>, map_res!(recognize!(preceded!(opt!(char!('-')), nom::digit)), str::parse));
-    named!(register<&str, Register>, verify!(nom::anychar, |c| c >= 'a' && c <= 'h'));
-    named!(value<&str, Value>, alt!(map!(num, Num) | map!(register, Reg)));
-
-    macro_rules! reg_op {
-        ($name : ident, $tag : expr, $variant : tt) => {
-            named!($name<&str, Instruction>, ws!(do_parse!(
-                        tag!($tag)  >>
-                        r: register >>
-                        v: value    >>
-                        ($variant(r, v))
-                        )));
-        }
-    }
-    reg_op!(iset, "set", Set);
-    reg_op!(isub, "sub", Sub);
-    reg_op!(imul, "mul", Mul);
-
-    named!(ijnz<&str, Instruction>, ws!(do_parse!(
-                tag!("jnz") >>
-                v1: value   >>
-                v2: value   >>
-                (Jnz(v1, v2))
-                )));
-
-    named!(pub parse_program<&str, Vec<Instruction>>,
-           complete!(many0!(alt!(iset | isub | imul | ijnz)))
-          );
-}
-
-use parse::parse_program;
-
-mod process {
-    use super::{Register, Value, Instruction};
-    use Value::*;
-    use Instruction::*;
-
-    struct Processor {
-        registers : [i64; 8]
-    }
-
-    macro_rules! bin_op {
-        ($name : ident, $op : tt) => {
-            fn $name(&mut self, r : Register, v : Value) {
-                let a = self.get(Reg(r));
-                let b = self.get(v);
-                self.set(r, Num(a $op b));
-            }
-        }
-    }
-
-    impl Processor {
-        fn new() -> Processor { Processor { registers: [0; 8] } }
-
-        fn get(&self, val : Value) -> i64 {
-            match val {
-                Num(v) => v,
-                Reg(r) => self.registers[(r as u8 - b'a') as usize]
-            }
-        }
-
-        fn set(&mut self, reg : Register, val : Value) {
-            self.registers[(reg as u8 - b'a') as usize] = self.get(val);
-        }
-
-        bin_op!(sub, -);
-        bin_op!(mul, *);
-    }
-
-    pub enum ProcessState {
-        Ran(Instruction),
-        Halt(i64),
-    }
-    use ProcessState::*;
-
-    pub struct Process<'a> {
-        processor : Processor,
-        ip : usize,
-        program : &'a [Instruction],
-    }
-
-    impl<'a> Process<'a> {
-        pub fn new(flag : i64, program : &'a [Instruction]) -> Process<'a> {
-            let mut processor = Processor::new();
-            processor.set('a', Num(flag));
-            Process { processor, ip: 0, program }
-        }
-
-        pub fn step(&mut self) -> ProcessState {
-            if self.ip >= self.program.len() {
-                return Halt(self.ip as i64);
-            }
-            let instruction = self.program[self.ip];
-            match instruction {
-                Set(x, y) => self.processor.set(x, y),
-                Sub(x, y) => self.processor.sub(x, y),
-                Mul(x, y) => self.processor.mul(x, y),
-                Jnz(x, y) => {
-                    let x = self.processor.get(x);
-                    if x != 0 {
-                        let y = self.processor.get(y);
-                        self.ip = (self.ip as i64 + y) as usize;
-                        return Ran(instruction);
-                    }
-                }
-            }
-            self.ip += 1;
-            Ran(instruction)
-        }
-
-        pub fn get_register(&self, reg : Register) -> i64 {
-            self.processor.get(Reg(reg))
-        }
-    }
-}
-
-use process::{Process, ProcessState};
-use ProcessState::*;
-
-fn run_mul_count(program : &[Instruction]) -> usize {
-    let mut mul_count = 0;
-    let mut process = Process::new(0, program);
-    loop {
-        match process.step() {
-            Ran(Mul(_, _)) => { mul_count += 1 },
-            Halt(_) => {
-                return mul_count;
-            },
-            _ => { }
-        }
-    }
-}
-
-fn print_program(program : &[Instruction]) {
-    let print_val = |v : Value| -> String {
-        match v {
-            Reg(r) => format!("{}", r as char),
-            Num(n) => format!("{}", n)
-        }
-    };
-    for (line, &i) in program.iter().enumerate() {
-        print!("{:2}: ", line + 1);
-        match i {
-            Set(r, v) => println!("{} = {};", r as char, print_val(v)),
-            Sub(r, v) => println!("{} -= {};", r as char, print_val(v)),
-            Mul(r, v) => println!("{} *= {};", r as char, print_val(v)),
-            Jnz(a, Num(o)) => println!("if ({} != 0)\n\tgoto {};", print_val(a), (line as i64 + o + 1)),
-            _ => eprintln!("unexpected instruction on line {}", line)
-        }
-    }
-}
-
-fn bruteforce(program : &[Instruction]) -> i64 {
-    let mut process = Process::new(1, program);
-    loop {
-        if let Halt(_) = process.step() {
-            return process.get_register('h');
-        }
-    }
-}
+use coprocessor_conflation::{Instruction, Puzzle, debug, decompile, optimize, parse_program, profile};
+use util::{Parts, args};
 
 fn main() {
     use clap::{App, Arg};
@@ -202,36 +21,151 @@ fn main() {
              .possible_values(&["label", "brute-force"])
              .default_value("label")
             )
-        .after_help("For solving part two, either manually decompile the program from the output of '--part2 label' \
+        .arg(Arg::with_name("input")
+             .long("input")
+             .value_name("FILE")
+             .help("Reads the program from FILE")
+            )
+        .arg(Arg::with_name("input-str")
+             .long("input-str")
+             .value_name("STRING")
+             .help("Uses STRING as the program")
+            )
+        .arg(Arg::with_name("bytecode")
+             .long("bytecode")
+             .value_name("FILE")
+             .conflicts_with_all(&["input", "input-str"])
+             .help("Reads the program from bytecode saved with --save-bytecode")
+            )
+        .arg(Arg::with_name("disassemble")
+             .long("disassemble")
+             .help("Prints the program back as text")
+            )
+        .arg(Arg::with_name("save-bytecode")
+             .long("save-bytecode")
+             .value_name("FILE")
+             .help("Saves the program as bytecode in FILE")
+            )
+        .arg(Arg::with_name("optimize")
+             .long("optimize")
+             .help("With --disassemble or --save-bytecode, runs the peephole optimizer on the program first")
+            )
+        .arg(Arg::with_name("format")
+             .long("format")
+             .value_name("FORMAT")
+             .possible_values(&["text", "json"])
+             .default_value("text")
+             .help("Prints the answer as text or as JSON")
+            )
+        .arg(Arg::with_name("timeout")
+             .long("timeout")
+             .value_name("SECONDS")
+             .help("Stops the brute force after SECONDS, printing the partial result")
+            )
+        .arg(Arg::with_name("max-steps")
+             .long("max-steps")
+             .value_name("N")
+             .help("Gives up after running N instructions")
+            )
+        .arg(Arg::with_name("detect-loops")
+             .long("detect-loops")
+             .help("Stops with an error if the program is provably in an infinite loop")
+            )
+        .arg(Arg::with_name("arithmetic")
+             .long("arithmetic")
+             .value_name("MODE")
+             .possible_values(&["checked", "wrapping", "saturating"])
+             .default_value("checked")
+             .help("Stops the program on overflow, or wraps or saturates")
+            )
+        .arg(Arg::with_name("debug")
+             .long("debug")
+             .help("Steps through the program in a debugger, with register a set for part two if --part2 is given")
+            )
+        .arg(Arg::with_name("profile")
+             .long("profile")
+             .help("Prints how many times each line runs, for part two if --part2 is given")
+            )
+        .arg(Arg::with_name("trace")
+             .long("trace")
+             .value_name("FILE")
+             .help("With --profile, writes every instruction executed to FILE")
+            )
+        .arg(Arg::with_name("steps")
+             .long("steps")
+             .value_name("N")
+             .help("With --profile, stops after N steps (default 1000000 for part two)")
+            )
+        .after_help("For solving part two, either read the algorithm from the decompiled program printed by '--part2 label' \
                     (see README.md) or brute force the solution with '--part2 brute-force' \
-                    (will take a very long time)")
+                    (which runs the program with its counting loops optimized into single instructions)")
         .get_matches();
 
-    let (label, brute) = if options.occurrences_of("part2") == 0 {
-        (false, false)
+    let (parts, label) = if options.occurrences_of("part2") == 0 {
+        (Parts::One, false)
     } else {
-        match options.value_of("part2") {
-            Some("label") => (true, false),
-            Some("brute-force") => (false, true),
-            _ => (false, false)
-        }
+        (Parts::Two, options.value_of("part2") == Some("label"))
     };
 
-    let run = |input : &str| {
-        let program = match parse_program(input).to_result() {
-            Ok(parsed) => parsed,
-            Err(_) => {
-                eprintln!("invalid input");
-                return;
-            },
-        };
-        if label {
-            print_program(&program);
-        } else if brute {
-            println!("value of h: {}", bruteforce(&program));
-        } else  {
-            println!("debug multiplications: {}", run_mul_count(&program));
+    let format = util::Format::parse(options.value_of("format").unwrap()).unwrap();
+    if let Some(timeout) = options.value_of("timeout") {
+        util::progress::set_timeout(Some(args::or_exit(util::progress::parse_timeout(timeout))));
+    }
+    let max_steps = args::or_exit(options.value_of("max-steps").map(vm::watchdog::parse_max_steps).transpose());
+    let config = vm::Config {
+        arithmetic: vm::Arithmetic::parse(options.value_of("arithmetic").unwrap()).unwrap(),
+        limits: vm::Limits { max_steps, detect_loops: options.is_present("detect-loops") },
+    };
+    // bytecode is run as loaded rather than parsed from its disassembly, which
+    // can't be done for the fused instructions of an optimized program
+    let bytecode = options.value_of("bytecode").map(|path| args::or_exit(vm::bytecode::load::<Instruction>(path)));
+    let source = util::InputSource::new(options.value_of("input"), options.value_of("input-str"), 23);
+    let read_program = || match bytecode {
+        Some(ref program) => Ok(program.clone()),
+        None => source.read().map_err(|e| e.to_string())
+            .and_then(|input| parse_program(&input).map_err(|e| e.to_string())),
+    };
+    if options.is_present("disassemble") || options.is_present("save-bytecode") {
+        let result = read_program()
+            .map(|program| if options.is_present("optimize") { optimize(&program) } else { program })
+            .and_then(|program| match options.value_of("save-bytecode") {
+                Some(path) => vm::bytecode::save(&program, path),
+                None => {
+                    print!("{}", vm::disassemble(&program));
+                    Ok(())
+                },
+            });
+        args::or_exit(result);
+        return;
+    }
+    if options.is_present("debug") {
+        debug(&args::or_exit(read_program()), parts == Parts::Two, config);
+        return;
+    }
+    if options.is_present("profile") {
+        let max_steps = args::or_exit(options.value_of("steps").map(|steps| {
+            steps.parse::<u64>().map_err(|_| format!("invalid number of steps: '{}'", steps))
+        }).transpose()).or(if parts == Parts::Two { Some(1_000_000) } else { None });
+        let report = read_program()
+            .and_then(|program| profile(&program, parts == Parts::Two, max_steps, options.value_of("trace"), config));
+        println!("{}", args::or_exit(report));
+        return;
+    }
+    // an interactive session goes on after an error, so that the input can be fixed
+    let interactive = bytecode.is_none() && source == util::InputSource::Stdin && util::is_tty();
+    let run = |program : Result<Vec<Instruction>, String>, parts : Parts| {
+        if parts == Parts::Two && label {
+            match program {
+                Ok(program) => print!("{}", decompile(&program)),
+                Err(e) if interactive => eprintln!("{}", e),
+                Err(e) => args::or_exit(Err(e)),
+            }
+        } else {
+            util::print_answers_with::<Puzzle, _>("", parts, format, |_| Ok(vm::Program { instructions: program?, config }));
         }
     };
-    util::run_multiline("enter program:", run);
+    match bytecode {
+        Some(program) => run(Ok(program), parts),
+        None => source.run_multiline("enter program:", parts, |input, parts| run(parse_program(input).map_err(|e| e.to_string()), parts)),
+    }
 }
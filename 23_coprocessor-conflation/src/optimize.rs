@@ -0,0 +1,253 @@
+//! A peephole optimizer that replaces the counting loops of coprocessor
+//! programs with fused instructions computing their result directly.
+//!
+//! A fused instruction replaces only the first instruction of its loop, and
+//! jumps past the end of the loop when it runs. The rest of the loop is left
+//! in place, so jumps into the middle of it still run the original code. If the
+//! registers are such that the loop wouldn't count up to its bound, the fused
+//! instruction does what the instruction it replaced would have.
+//!
+//! The same goes for loops where some value could overflow, so that the
+//! arithmetic mode (see `vm::arith`) applies as it would to the original loop.
+//!
+//! Fused instructions don't execute the `mul`s of their loops, so the optimized
+//! program can't be used to count them.
+
+use vm::{Effect, Registers};
+
+use {Instruction, Register, Value};
+use Instruction::*;
+use Value::*;
+
+/// `sub e -1; set g e; sub g b; jnz g -3`
+const COUNT_LOOP : usize = 4;
+/// `set g d; mul g e; sub g b; jnz g 2; set f 0;` followed by a count loop
+/// jumping back 8.
+const DIVISOR_LOOP : usize = 9;
+/// `set e e0;` a divisor loop, then `sub d -1; set g d; sub g b; jnz g -13`
+const COMPOSITE_LOOP : usize = 14;
+
+/// Replaces every loop the optimizer recognizes with a fused instruction.
+pub fn optimize(program : &[Instruction]) -> Vec<Instruction> {
+    (0..program.len())
+        .map(|i| {
+            let code = &program[i..];
+            composite_loop(code)
+                .or_else(|| divisor_loop(code))
+                .or_else(|| count_loop(code))
+                .unwrap_or(program[i])
+        })
+        .collect()
+}
+
+/// True if no two of the registers are the same.
+fn distinct(registers : &[Register]) -> bool {
+    registers.iter().enumerate().all(|(i, r)| ! registers[i + 1..].contains(r))
+}
+
+/// True if `v` is a number or a register other than `registers`, i.e. it isn't
+/// changed by the loop.
+fn constant(v : Value, registers : &[Register]) -> bool {
+    match v {
+        Num(_) => true,
+        Reg(r) => ! registers.contains(&r),
+    }
+}
+
+fn count_loop(code : &[Instruction]) -> Option<Instruction> {
+    match *code {
+        [Sub(e, Num(-1)), Set(g, Reg(e2)), Sub(g2, b), Jnz(Reg(g3), Num(-3)), ..]
+            if e == e2 && g == g2 && g == g3 && distinct(&[e, g]) && constant(b, &[e, g]) =>
+            Some(CountLoop { e, b, g }),
+        _ => None,
+    }
+}
+
+fn divisor_loop(code : &[Instruction]) -> Option<Instruction> {
+    match *code {
+        [Set(g, d), Mul(g2, Reg(e)), Sub(g3, b), Jnz(Reg(g4), Num(2)), Set(f, Num(0)),
+         Sub(e2, Num(-1)), Set(g5, Reg(e3)), Sub(g6, b2), Jnz(Reg(g7), Num(-8)), ..]
+            if [g2, g3, g4, g5, g6, g7].iter().all(|&r| r == g) && e == e2 && e == e3 && b == b2
+                && distinct(&[g, e, f]) && constant(d, &[g, e, f]) && constant(b, &[g, e, f]) =>
+            Some(DivisorLoop { d, e, b, f, g }),
+        _ => None,
+    }
+}
+
+fn composite_loop(code : &[Instruction]) -> Option<Instruction> {
+    if code.len() < COMPOSITE_LOOP {
+        return None;
+    }
+    match (code[0], divisor_loop(&code[1..]), &code[1 + DIVISOR_LOOP..COMPOSITE_LOOP]) {
+        (Set(e, e0), Some(DivisorLoop { d: Reg(d), e: e2, b, f, g }),
+         &[Sub(d2, Num(-1)), Set(g2, Reg(d3)), Sub(g3, b2), Jnz(Reg(g4), Num(-13))])
+            if e == e2 && d == d2 && d == d3 && g == g2 && g == g3 && g == g4 && b == b2
+                && distinct(&[d, e, f, g]) && constant(e0, &[d, e, f, g]) =>
+            Some(CompositeLoop { d, e0, e, b, f, g }),
+        _ => None,
+    }
+}
+
+/// Runs a fused instruction.
+pub fn execute(instruction : Instruction, registers : &mut Registers) -> Effect {
+    match instruction {
+        CountLoop { e, b, g } => {
+            let (e_val, b) = (registers.get(Reg(e)), registers.get(b));
+            if e_val >= b || e_val.checked_sub(b).is_none() {
+                return registers.sub(e, Num(-1));
+            }
+            registers.set(e, b);
+            registers.set(g, 0);
+            Effect::Jump(COUNT_LOOP as i64)
+        },
+        DivisorLoop { d, e, b, f, g } => {
+            let (d, e_val, b) = (registers.get(d), registers.get(Reg(e)), registers.get(b));
+            // d * e - b is monotonic in e, so it fits for every e if it does for the first and last
+            let fits = |e : i64| d.checked_mul(e).and_then(|g| g.checked_sub(b)).is_some();
+            if e_val >= b || e_val.checked_sub(b).is_none() || ! fits(e_val) || ! fits(b - 1) {
+                registers.set(g, d);
+                return Effect::Next;
+            }
+            if has_factor(d, e_val, b) {
+                registers.set(f, 0);
+            }
+            registers.set(e, b);
+            registers.set(g, 0);
+            Effect::Jump(DIVISOR_LOOP as i64)
+        },
+        CompositeLoop { d, e0, e, b, f, g } => {
+            let (d_val, e0, b) = (registers.get(Reg(d)), registers.get(e0), registers.get(b));
+            if d_val < 1 || e0 < 1 || d_val >= b || e0 >= b || (b - 1).checked_mul(b - 1).is_none() {
+                registers.set(e, e0);
+                return Effect::Next;
+            }
+            if is_product(d_val, e0, b) {
+                registers.set(f, 0);
+            }
+            registers.set(d, b);
+            registers.set(e, b);
+            registers.set(g, 0);
+            Effect::Jump(COMPOSITE_LOOP as i64)
+        },
+        _ => unreachable!("not a fused instruction: {:?}", instruction),
+    }
+}
+
+/// True if `d * x == b` for some `x` from `from` up to (but not including) `b`.
+fn has_factor(d : i64, from : i64, b : i64) -> bool {
+    if d == 0 {
+        return b == 0;
+    }
+    b.checked_rem(d) == Some(0) && (from..b).contains(&(b / d))
+}
+
+/// True if `x * y == b` for some positive `x` from `x_from` and `y` from `y_from`,
+/// both up to (but not including) `b`.
+fn is_product(x_from : i64, y_from : i64, b : i64) -> bool {
+    let fits = |x : i64, y : i64| x >= x_from && x < b && y >= y_from && y < b;
+    (1..).take_while(|&i| i * i <= b)
+        .filter(|&i| b % i == 0)
+        .any(|i| fits(i, b / i) || fits(b / i, i))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use {parse_program, new_process};
+    use vm::Config;
+
+    fn run(program : &[Instruction], flag : i64) -> Registers {
+        let mut process = new_process(flag, program, Config::default());
+        while let ::vm::State::Ran(_) = process.step() { }
+        process.registers().clone()
+    }
+
+    /// The puzzle program, with a smaller range of numbers for part two.
+    const PROGRAM : &str = "\
+set b 67
+set c b
+jnz a 2
+jnz 1 5
+mul b 1
+sub b -10
+set c b
+sub c -170
+set f 1
+set d 2
+set e 2
+set g d
+mul g e
+sub g b
+jnz g 2
+set f 0
+sub e -1
+set g e
+sub g b
+jnz g -8
+sub d -1
+set g d
+sub g b
+jnz g -13
+jnz f 2
+sub h -1
+set g b
+sub g c
+jnz g 2
+jnz 1 3
+sub b -17
+jnz 1 -23";
+
+    #[test]
+    fn test_puzzle() {
+        let program = parse_program(PROGRAM).unwrap();
+        let optimized = optimize(&program);
+        assert!(matches!(optimized[10], CompositeLoop { d: 'd', e0: Num(2), e: 'e', b: Reg('b'), f: 'f', g: 'g' }));
+        assert!(matches!(optimized[11], DivisorLoop { .. }));
+        for flag in 0..2 {
+            let registers = run(&program, flag);
+            assert_eq!(run(&optimized, flag), registers);
+        }
+        assert_eq!(run(&optimized, 1).get(Reg('h')), 10);
+    }
+
+    #[test]
+    fn test_overflow() {
+        // d * e overflows for e = 5, before e gets to b
+        let input = format!("set d {}\nset e 2\nset b 10\nset f 1\nset g d\nmul g e\nsub g b\njnz g 2\nset f 0\n\
+                             sub e -1\nset g e\nsub g b\njnz g -8", i64::MAX / 4);
+        let optimized = optimize(&parse_program(&input).unwrap());
+        let mut process = new_process(0, &optimized, Config::default());
+        let state = loop {
+            match process.step() {
+                ::vm::State::Ran(_) => {},
+                state => break state,
+            }
+        };
+        assert_eq!(state, ::vm::State::Faulted(5, ::vm::Fault::Overflow));
+        assert_eq!(process.get_register('e'), 5);
+    }
+
+    #[test]
+    fn test_loops() {
+        let programs = [
+            "set e 3\nset b 10\nsub e -1\nset g e\nsub g b\njnz g -3",
+            "set d 3\nset e 2\nset b 12\nset f 1\nset g d\nmul g e\nsub g b\njnz g 2\nset f 0\nsub e -1\nset g e\nsub g b\njnz g -8",
+            "set d 5\nset e 2\nset b 12\nset f 1\nset g d\nmul g e\nsub g b\njnz g 2\nset f 0\nsub e -1\nset g e\nsub g b\njnz g -8",
+            "set d 0\nset e -2\nset b 3\nset f 1\nset g d\nmul g e\nsub g b\njnz g 2\nset f 0\nsub e -1\nset g e\nsub g b\njnz g -8",
+        ];
+        for (i, input) in programs.iter().enumerate() {
+            let program = parse_program(input).unwrap();
+            let optimized = optimize(&program);
+            assert!(optimized.iter().any(|i| matches!(*i, CountLoop { .. } | DivisorLoop { .. })), "program {} wasn't optimized", i);
+            assert_eq!(run(&optimized, 0), run(&program, 0), "program {}", i);
+        }
+        for &(d, e, b) in &[(2, 2, 49), (7, 7, 49), (8, 2, 49), (2, 3, 53), (3, 2, 6), (2, 6, 12)] {
+            let input = format!("set d {}\nset b {}\nset f 1\nset e {}\nset g d\nmul g e\nsub g b\njnz g 2\nset f 0\n\
+                                 sub e -1\nset g e\nsub g b\njnz g -8\nsub d -1\nset g d\nsub g b\njnz g -13", d, b, e);
+            let program = parse_program(&input).unwrap();
+            let optimized = optimize(&program);
+            assert!(matches!(optimized[3], CompositeLoop { .. }));
+            assert_eq!(run(&optimized, 0), run(&program, 0), "d = {}, e = {}, b = {}", d, e, b);
+        }
+    }
+}
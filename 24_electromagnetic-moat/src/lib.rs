@@ -0,0 +1,99 @@
+extern crate util;
+
+use util::{ParseError, Solution};
+use util::parse::parse_word;
+
+pub type Component = (u32, u32);
+
+fn add_strength(components : &[Component], used : &mut [bool], socket : u32, strength : u32) -> u32 {
+    let mut strongest = strength;
+    for (n, (i, o)) in components.iter().cloned().enumerate().filter(|&(_, (i, o))| i == socket || o == socket) {
+        if ! used[n] {
+            used[n] = true;
+            let new_socket = if i == socket { o } else { i };
+            let s = add_strength(components, used, new_socket, i + o + strength);
+            strongest = strongest.max(s);
+            used[n] = false;
+        }
+    }
+    strongest
+}
+
+fn add_length(components : &[Component], used : &mut [bool], socket : u32, strength : u32, length : u32) -> (u32, u32) {
+    let mut longest = length;
+    let mut strongest = strength;
+    for (n, (i, o)) in components.iter().cloned().enumerate().filter(|&(_, (i, o))| i == socket || o == socket) {
+        if ! used[n] {
+            used[n] = true;
+            let new_socket = if i == socket { o } else { i };
+            let (s, l) = add_length(components, used, new_socket, i + o + strength, 1 + length);
+            if l > longest {
+                longest = l;
+                strongest = s;
+            } else if l == longest {
+                strongest = strongest.max(s);
+            }
+            used[n] = false;
+        }
+    }
+    (strongest, longest)
+}
+
+fn get_strongest(input : &[(u32, u32)]) -> u32 {
+    let mut used = vec![false; input.len()];
+    add_strength(input, &mut used, 0, 0)
+}
+
+fn get_longest(input : &[(u32, u32)]) -> (u32, u32) {
+    let mut used = vec![false; input.len()];
+    add_length(input, &mut used, 0, 0, 0)
+}
+
+fn parse_input(input : &str) -> Result<Vec<Component>, ParseError> {
+    let mut components = Vec::new();
+    for line in input.lines().filter(|line| ! line.trim().is_empty()) {
+        let sep = match line.find('/') {
+            Some(i) => i,
+            None => return Err(ParseError::at(input, &line[line.len()..], "'/'"))
+        };
+        let (n1, n2) = line.split_at(sep);
+        let s1 = parse_word(input, n1.trim(), "port size")?;
+        let s2 = parse_word(input, n2[1..].trim(), "port size")?;
+        components.push((s1, s2))
+    }
+    Ok(components)
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const DAY : u32 = 24;
+
+    type Input = Vec<Component>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+    const LABEL1 : &'static str = "strongest bridge";
+    const LABEL2 : &'static str = "longest bridge strength";
+
+    fn parse(input : &str) -> Result<Vec<Component>, String> { Ok(parse_input(input)?) }
+
+    fn part1(components : &Vec<Component>) -> Result<u32, String> { Ok(get_strongest(components)) }
+
+    fn part2(components : &Vec<Component>) -> Result<u32, String> { Ok(get_longest(components).0) }
+}
+
+#[test]
+fn test_example() {
+    let input =
+"0/2
+2/2
+2/3
+3/4
+3/5
+0/1
+10/1
+9/10";
+    let components = parse_input(input).unwrap();
+    assert_eq!(31, get_strongest(&components));
+    assert_eq!((19, 4), get_longest(&components));
+}
@@ -0,0 +1,99 @@
+extern crate util;
+
+use util::{ParseError, Solution};
+use util::parse::parse_word;
+
+enum State {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+}
+use State::*;
+
+fn parse_input(input : &str) -> Result<usize, ParseError> {
+    parse_word(input, input.trim(), "number of steps")
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const DAY : u32 = 25;
+
+    type Input = usize;
+    type Answer1 = usize;
+    type Answer2 = usize;
+    const LABEL1 : &'static str = "checksum";
+
+    fn parse(input : &str) -> Result<usize, String> { Ok(parse_input(input)?) }
+
+    fn part1(&steps : &usize) -> Result<usize, String> {
+        Ok(machine(steps).iter().cloned().filter(|&c| c == 1).count())
+    }
+
+    fn part2(_ : &usize) -> Result<usize, String> {
+        Err(String::from("there is no part two on day 25"))
+    }
+}
+
+macro_rules! define_machine (
+    ($name:ident : $($state:pat => $val0:expr, $mov0:expr, $state0:expr ; $val1:expr, $mov1:expr, $state1:expr)*) => {
+        fn $name(steps : usize) -> Vec<u8> {
+            let mut tape = vec![0; 1024];
+            let mut state = A;
+            let mut cursor : isize = 0;
+
+            for _ in 0..steps {
+                if cursor < 0 {
+                    let len = tape.len();
+                    tape.resize(len * 2, 0);
+                    for i in 0..len {
+                        tape.swap(i, i + len);
+                    }
+                    cursor += len as isize;
+                } else if cursor >= tape.len() as isize {
+                    let len = tape.len();
+                    tape.resize(len * 2, 0);
+                }
+                #[allow(unreachable_patterns)]
+                match state {
+                    $($state => 
+                      if tape[cursor as usize] == 0 {
+                          tape[cursor as usize] = $val0;
+                          cursor += $mov0;
+                          state = $state0;
+                      } else {
+                          tape[cursor as usize] = $val1;
+                          cursor += $mov1;
+                          state = $state1;
+                      }
+                     ),*
+                    _ => { }
+                }
+            }
+            tape
+        }
+    };
+);
+
+define_machine!(machine :
+                A => 1,  1, B ; 0, -1, B
+                B => 1, -1, C ; 0,  1, E
+                C => 1,  1, E ; 0, -1, D
+                D => 1, -1, A ; 1, -1, A
+                E => 0,  1, A ; 0,  1, F
+                F => 1,  1, E ; 1,  1, A
+               );
+
+#[cfg(test)]
+define_machine!(test_machine :
+                A => 1,  1, B ; 0, -1, B
+                B => 1, -1, A ; 1,  1, A
+                );
+
+#[test]
+fn test_example() {
+    assert_eq!(3, test_machine(6).iter().cloned().filter(|&c| c == 1).count());
+}
@@ -0,0 +1,61 @@
+extern crate util;
+
+use util::{ParseError, Solution};
+use util::parse::parse_words;
+
+fn checksum_diff(nums : &[i32]) -> i32 {
+    let mut max = i32::MIN;
+    let mut min = i32::MAX;
+    for &i in nums {
+        if i > max {
+            max = i;
+        }
+        if i < min {
+            min = i;
+        }
+    }
+    max - min
+}
+
+fn checksum_div(nums : &[i32]) -> i32 {
+    let mut iter = nums.iter();
+    while let Some(x) = iter.next() {
+        for y in iter.clone() {
+            if x >= y && x % y == 0 {
+                return x / y;
+            } else if x < y && y % x == 0 {
+                return y / x;
+            }
+        }
+    }
+    0
+}
+
+fn parse_input(input : &str) -> Result<Vec<Vec<i32>>, ParseError> {
+    input.lines()
+        .filter(|line| ! line.trim().is_empty())
+        .map(|line| parse_words(input, line, "number"))
+        .collect()
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const DAY : u32 = 2;
+
+    type Input = Vec<Vec<i32>>;
+    type Answer1 = i32;
+    type Answer2 = i32;
+    const LABEL1 : &'static str = "Checksum";
+    const LABEL2 : &'static str = "Checksum";
+
+    fn parse(input : &str) -> Result<Vec<Vec<i32>>, String> { Ok(parse_input(input)?) }
+
+    fn part1(sheet : &Vec<Vec<i32>>) -> Result<i32, String> {
+        Ok(sheet.iter().map(|row| checksum_diff(row)).sum())
+    }
+
+    fn part2(sheet : &Vec<Vec<i32>>) -> Result<i32, String> {
+        Ok(sheet.iter().map(|row| checksum_div(row)).sum())
+    }
+}
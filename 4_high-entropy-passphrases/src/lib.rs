@@ -0,0 +1,65 @@
+extern crate util;
+
+use std::collections::hash_set::HashSet;
+use util::{ParseError, Solution};
+
+// Assuming no more that 255 instances of the same character in a word.
+#[derive(PartialEq, Eq, Hash)]
+struct AnagramSet {
+    count : [u8; 26],
+}
+
+impl<'a> From<&'a str> for AnagramSet {
+    fn from(s : &'a str) -> Self {
+        let mut aset = AnagramSet{count: [0; 26]};
+        for &c in s.as_bytes() {
+            if c.is_ascii_lowercase() {
+                aset.count[(c - b'a') as usize] += 1;
+            }
+        }
+        aset
+    }
+}
+
+fn is_valid<'a, T : Eq + std::hash::Hash + From<&'a str>>(passphrase : &'a str) -> bool {
+    let mut hs = HashSet::<T>::new();
+    for word in passphrase.split_whitespace() {
+        if ! hs.insert(T::from(word)) {
+            return false;
+        }
+    }
+    true
+}
+
+fn parse_input(input : &str) -> Result<Vec<String>, ParseError> {
+    let mut passphrases = Vec::new();
+    for line in input.lines().filter(|line| line.split_whitespace().count() > 0) {
+        if let Some(word) = line.split_whitespace().find(|word| ! word.bytes().all(|c| c.is_ascii_lowercase())) {
+            return Err(ParseError::at(input, word, "lowercase word"));
+        }
+        passphrases.push(String::from(line));
+    }
+    Ok(passphrases)
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const DAY : u32 = 4;
+
+    type Input = Vec<String>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+    const LABEL1 : &'static str = "valid";
+    const LABEL2 : &'static str = "valid";
+
+    fn parse(input : &str) -> Result<Vec<String>, String> { Ok(parse_input(input)?) }
+
+    fn part1(passphrases : &Vec<String>) -> Result<usize, String> {
+        Ok(passphrases.iter().filter(|p| is_valid::<String>(p)).count())
+    }
+
+    fn part2(passphrases : &Vec<String>) -> Result<usize, String> {
+        Ok(passphrases.iter().filter(|p| is_valid::<AnagramSet>(p)).count())
+    }
+}
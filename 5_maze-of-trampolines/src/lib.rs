@@ -0,0 +1,56 @@
+extern crate util;
+
+use util::{ParseError, Solution};
+use util::parse::parse_words;
+
+fn count_steps(jump_list : &mut [i32]) -> usize {
+    let mut index : i32 = 0;
+    let mut count : usize = 0;
+    let len = jump_list.len() as i32;
+    while index >= 0 && index < len {
+        let temp = index as usize;
+        index += jump_list[temp];
+        jump_list[temp] += 1;
+        count += 1;
+    }
+    count
+}
+
+fn count_steps_limit(jump_list : &mut [i32]) -> usize {
+    let mut index : i32 = 0;
+    let mut count : usize = 0;
+    let len = jump_list.len() as i32;
+    while index >= 0 && index < len {
+        let temp = index as usize;
+        index += jump_list[temp];
+        if jump_list[temp] < 3 {
+            jump_list[temp] += 1;
+        } else {
+            jump_list[temp] -= 1;
+        }
+        count += 1;
+    }
+    count
+}
+
+fn parse_input(input : &str) -> Result<Vec<i32>, ParseError> {
+    parse_words(input, input, "jump offset")
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const DAY : u32 = 5;
+
+    type Input = Vec<i32>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+    const LABEL1 : &'static str = "steps";
+    const LABEL2 : &'static str = "steps";
+
+    fn parse(input : &str) -> Result<Vec<i32>, String> { Ok(parse_input(input)?) }
+
+    fn part1(jump_list : &Vec<i32>) -> Result<usize, String> { Ok(count_steps(&mut jump_list.clone())) }
+
+    fn part2(jump_list : &Vec<i32>) -> Result<usize, String> { Ok(count_steps_limit(&mut jump_list.clone())) }
+}
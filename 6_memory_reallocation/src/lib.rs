@@ -0,0 +1,73 @@
+extern crate util;
+
+use util::{ParseError, Solution};
+use util::cycle::{self, Cycle};
+use util::parse::parse_words;
+
+type MemorySet = [u16];
+
+fn redistribute(banks : &mut MemorySet) {
+    let size = banks.len() as u16;
+    if size == 0 {
+        return;
+    }
+    let mut max = 0;
+    let mut index = 0;
+    for (i, &b) in banks.iter().enumerate() {
+        if b > max {
+            max = b;
+            index = i;
+        }
+    }
+    let fill = max / size;
+    let mut extra = max % size;
+    banks[index] = fill;
+    let mut distribute = |start, end| {
+        for b in banks[start..end].iter_mut() {
+            *b += fill;
+            if extra > 0 {
+                *b += 1;
+                extra -= 1;
+            }
+        }
+    };
+    distribute(index + 1, size as usize);
+    distribute(0, index);
+}
+
+fn find_cycle(banks : &MemorySet) -> Cycle {
+    let (cycle, _) = cycle::hashed(banks.to_vec(), |banks| redistribute(banks));
+    cycle
+}
+
+fn parse_input(input : &str) -> Result<Vec<u16>, ParseError> {
+    parse_words(input, input, "block count")
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const DAY : u32 = 6;
+
+    type Input = Vec<u16>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+    const LABEL1 : &'static str = "redistributions";
+    const LABEL2 : &'static str = "cycle length";
+
+    fn parse(input : &str) -> Result<Vec<u16>, String> { Ok(parse_input(input)?) }
+
+    fn part1(banks : &Vec<u16>) -> Result<usize, String> {
+        let cycle = find_cycle(banks);
+        Ok(cycle.start + cycle.length)
+    }
+
+    fn part2(banks : &Vec<u16>) -> Result<usize, String> { Ok(find_cycle(banks).length) }
+}
+
+#[test]
+fn test_example() {
+    let cycle = find_cycle(&[0, 2, 7, 0]);
+    assert_eq!(5, cycle.start + cycle.length);
+    assert_eq!(4, cycle.length);
+}
@@ -0,0 +1,135 @@
+#[macro_use] extern crate nom;
+extern crate petgraph;
+extern crate util;
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use nom::alpha;
+use petgraph::{Graph, Direction};
+use petgraph::graph::NodeIndex;
+use util::{ParseError, Solution};
+use util::parse::parse_lines;
+
+named!(weight<&str, u32>, ws!(delimited!(tag!("("), map_res!(nom::digit, FromStr::from_str), tag!(")"))));
+named!(children<&str, Vec<&str>>, preceded!(ws!(tag!("->")), separated_nonempty_list_complete!(ws!(char!(',')), alpha)));
+named!(parse_vertex<&str, (&str, u32, Option<Vec<&str>>)>, ws!(tuple!(alpha, weight, opt!(complete!(children)))));
+
+pub struct Program {
+    name: String,
+    weight: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Weight {
+    weight : u32,
+    extra : u32,
+}
+
+impl Weight {
+    fn total(&self) -> u32 { self.weight + self.extra }
+}
+
+#[derive(Debug)]
+enum BalanceResult {
+    Balanced(Weight),
+    Unbalanced(u32),
+}
+
+use BalanceResult::{Balanced, Unbalanced};
+
+pub type ProgramTree = Graph<Program, ()>;
+
+fn check_balanced(tree : &ProgramTree, root : NodeIndex) -> BalanceResult {
+    let weight = tree[root].weight;
+    let mut neighbors = tree.neighbors(root).peekable();
+    if neighbors.peek().is_none() {
+        return Balanced(Weight { weight, extra: 0 });
+    }
+    let mut neighbor_weights = Vec::new();
+    for n in neighbors {
+        match check_balanced(tree, n) {
+            ub@Unbalanced(_) => return ub,
+            Balanced(weight) => neighbor_weights.push(weight),
+        }
+    }
+    if neighbor_weights.len() < 2 {
+        let extra = neighbor_weights.first().map_or(0, Weight::total) + neighbor_weights.get(1).map_or(0, Weight::total);
+        return Balanced(Weight { weight, extra });
+    }
+    let mut primary = neighbor_weights[0];
+    let mut secondary = neighbor_weights[1];
+    let mut primary_weight = primary.total();
+    for &w in neighbor_weights[2..].iter() {
+        if w.total() != primary_weight {
+            if w.total() == secondary.total() {
+                secondary = primary;
+                primary = w;
+                primary_weight = w.total();
+            } else {
+                secondary = w;
+            }
+        }
+    }
+    if secondary.total() != primary_weight {
+        Unbalanced((secondary.weight + primary_weight) - secondary.total())
+    } else {
+        Balanced(Weight { weight, extra: primary_weight * (neighbor_weights.len() as u32) })
+    }
+}
+
+fn get_root(tree : &ProgramTree) -> Option<NodeIndex> {
+    if let Some(prog) = tree.node_indices().next() {
+        let mut root = prog;
+        while let Some(prog) = tree.neighbors_directed(root, Direction::Incoming).next() {
+            root = prog;
+        };
+        Some(root)
+    } else {
+        None
+    }
+}
+
+fn parse_input(input : &str) -> Result<ProgramTree, ParseError> {
+    let mut nodes = BTreeMap::<String, NodeIndex>::new();
+    let mut tree = ProgramTree::new();
+    for (name, weight, children) in parse_lines(input, parse_vertex)? {
+        {
+            let node = nodes.entry(String::from(name)).or_insert_with(|| tree.add_node(Program { name: String::from(name), weight }));
+            tree[*node].weight = weight;
+        }
+        if let Some(ref children) = children {
+            for &c in children {
+                nodes.entry(String::from(c)).or_insert_with(|| tree.add_node(Program { name: String::from(c), weight: 0 }));
+                tree.add_edge(nodes[name], nodes[c], ());
+            }
+        }
+    }
+    Ok(tree)
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const DAY : u32 = 7;
+
+    type Input = ProgramTree;
+    type Answer1 = String;
+    type Answer2 = u32;
+    const LABEL1 : &'static str = "Root";
+    const LABEL2 : &'static str = "corrected weight";
+
+    fn parse(input : &str) -> Result<ProgramTree, String> { Ok(parse_input(input)?) }
+
+    fn part1(tree : &ProgramTree) -> Result<String, String> {
+        let root = get_root(tree).ok_or("empty tree")?;
+        Ok(tree[root].name.clone())
+    }
+
+    fn part2(tree : &ProgramTree) -> Result<u32, String> {
+        let root = get_root(tree).ok_or("empty tree")?;
+        match check_balanced(tree, root) {
+            Balanced(_) => Err(String::from("tree is already balanced")),
+            Unbalanced(weight) => Ok(weight),
+        }
+    }
+}
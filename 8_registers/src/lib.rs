@@ -0,0 +1,218 @@
+#[macro_use] extern crate nom;
+extern crate regex;
+extern crate util;
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use util::{ParseError, Solution};
+use util::parse::parse_lines;
+
+named!(integer<&str, i64>, map_res!(re_find!("^-?[0-9]+"), FromStr::from_str));
+named!(register<&str, Register>, map!(re_find!("^[a-zA-Z_][a-zA-Z0-9_]*"), String::from));
+named!(parse_instruction<&str, Instruction>, ws!(do_parse!(
+            target: register >>
+            op: map_res!(nom::alpha, FromStr::from_str) >>
+            amount: integer >>
+            tag_no_case!("if") >>
+            test: register >>
+            // NB: parsers are applied from left to right, so "<=" MUST come before "<" to parse correctly!
+            // Got the wrong answer because of this.
+            cond: map_res!(alt!(tag!("<=") | tag!("<") | tag!("==") | tag!("!=") | tag!(">=") | tag!(">")), FromStr::from_str) >>
+            value: integer >>
+            (Instruction { register: target, op, amount, test, cond, value })
+            )));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cond {
+    LT,
+    LE,
+    EQ,
+    NE,
+    GE,
+    GT,
+}
+
+impl FromStr for Cond {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "<" => Ok(Cond::LT),
+            "<=" => Ok(Cond::LE),
+            "==" => Ok(Cond::EQ),
+            "!=" => Ok(Cond::NE),
+            ">=" => Ok(Cond::GE),
+            ">" => Ok(Cond::GT),
+            _ => Err("invalid conditional operator"),
+        }
+    }
+}
+
+impl Cond {
+    fn check(&self, a : i64, b : i64) -> bool {
+        use Cond::*;
+        match *self {
+            LT => a < b,
+            LE => a <= b,
+            EQ => a == b,
+            NE => a != b,
+            GE => a >= b,
+            GT => a > b,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Inc,
+    Dec,
+}
+
+use Op::*;
+
+impl FromStr for Op {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_ref() {
+            "INC" => Ok(Inc),
+            "DEC" => Ok(Dec),
+            _ => Err("invalid operation"),
+        }
+    }
+}
+
+pub type Register = String;
+
+/// `register op amount if test cond value`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub register : Register,
+    pub op : Op,
+    pub amount : i64,
+    pub test : Register,
+    pub cond : Cond,
+    pub value : i64,
+}
+
+/// The result of running a program.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Execution {
+    /// The final value of every register named in the program.
+    pub registers : BTreeMap<Register, i64>,
+    /// The largest value held by any register while the program ran, or 0 if
+    /// none was ever positive.
+    pub max_held : i64,
+    /// The values each register held, starting from 0, with a new value each
+    /// time an instruction changes it.
+    pub history : BTreeMap<Register, Vec<i64>>,
+}
+
+impl Execution {
+    /// The largest final register value, or 0 if there are no registers.
+    pub fn max(&self) -> i64 {
+        self.registers.values().cloned().max().unwrap_or(0)
+    }
+}
+
+/// Runs the program, failing if a register overflows.
+pub fn execute(program : &[Instruction]) -> Result<Execution, String> {
+    let mut execution = Execution::default();
+    for (i, instruction) in program.iter().enumerate() {
+        for r in &[&instruction.register, &instruction.test] {
+            if ! execution.registers.contains_key(*r) {
+                execution.registers.insert(r.to_string(), 0);
+                execution.history.insert(r.to_string(), vec![0]);
+            }
+        }
+        if ! instruction.cond.check(execution.registers[&instruction.test], instruction.value) {
+            continue;
+        }
+        let value = execution.registers.get_mut(&instruction.register).unwrap();
+        let old = *value;
+        *value = match instruction.op {
+            Inc => value.checked_add(instruction.amount),
+            Dec => value.checked_sub(instruction.amount),
+        }.ok_or_else(|| format!("register {} overflows at instruction {}", instruction.register, i + 1))?;
+        execution.max_held = i64::max(execution.max_held, *value);
+        if *value != old {
+            execution.history.get_mut(&instruction.register).unwrap().push(*value);
+        }
+    }
+    Ok(execution)
+}
+
+/// Parses a program with one instruction per line.
+pub fn parse_program(input : &str) -> Result<Vec<Instruction>, ParseError> {
+    parse_lines(input, parse_instruction)
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const DAY : u32 = 8;
+
+    type Input = Vec<Instruction>;
+    type Answer1 = i64;
+    type Answer2 = i64;
+    const LABEL1 : &'static str = "max register value";
+    const LABEL2 : &'static str = "max held";
+
+    fn parse(input : &str) -> Result<Vec<Instruction>, String> { Ok(parse_program(input)?) }
+
+    fn part1(program : &Vec<Instruction>) -> Result<i64, String> { Ok(execute(program)?.max()) }
+
+    fn part2(program : &Vec<Instruction>) -> Result<i64, String> { Ok(execute(program)?.max_held) }
+}
+
+#[test]
+fn test_integer_parser() {
+    use nom::*;
+    let empty = "";
+    assert_eq!(integer("0"), IResult::Done(empty, 0));
+    assert_eq!(integer("5"), IResult::Done(empty, 5));
+    assert_eq!(integer("1234"), IResult::Done(empty, 1234));
+    assert_eq!(integer("-1234"), IResult::Done(empty, -1234));
+}
+
+#[test]
+fn test_register_parser() {
+    use nom::*;
+    let empty = "";
+    assert_eq!(register("a"), IResult::Done(empty, String::from("a")));
+    assert_eq!(register("ab"), IResult::Done(empty, String::from("ab")));
+    assert_eq!(register("abc"), IResult::Done(empty, String::from("abc")));
+    assert_eq!(register("a_long_name2"), IResult::Done(empty, String::from("a_long_name2")));
+}
+
+#[test]
+fn test_instruction_parser() {
+    use nom::*;
+    let empty = "";
+    let instruction = |register : &str, op, amount, test : &str, cond, value| {
+        Instruction { register: String::from(register), op, amount, test: String::from(test), cond, value }
+    };
+    assert_eq!(parse_instruction("a inc 5 if b < 10"), IResult::Done(empty, instruction("a", Op::Inc, 5, "b", Cond::LT, 10)));
+    assert_eq!(parse_instruction("a inc 5 if b <= 10"), IResult::Done(empty, instruction("a", Op::Inc, 5, "b", Cond::LE, 10)));
+    assert_eq!(parse_instruction("abc DEC 5 IF xyz != -30"), IResult::Done(empty, instruction("abc", Op::Dec, 5, "xyz", Cond::NE, -30)));
+    assert_eq!(parse_instruction("counter inc 5000000000 if other_reg >= -1"),
+               IResult::Done(empty, instruction("counter", Op::Inc, 5_000_000_000, "other_reg", Cond::GE, -1)));
+}
+
+#[test]
+fn test_execute() {
+    let program = parse_program(include_str!("../example.txt")).unwrap();
+    let execution = execute(&program).unwrap();
+    assert_eq!((execution.max(), execution.max_held), (1, 10));
+    let registers : Vec<(&str, i64)> = execution.registers.iter().map(|(r, &v)| (r.as_str(), v)).collect();
+    assert_eq!(registers, vec![("a", 1), ("b", 0), ("c", -10)]);
+    assert_eq!(execution.history["a"], vec![0, 1]);
+    assert_eq!(execution.history["b"], vec![0]);
+    assert_eq!(execution.history["c"], vec![0, 10, -10]);
+
+    let program = parse_program("a inc 0 if a == 0\na dec 0 if a == 0\na inc 2 if a == 0\nb dec 0 if a > 0").unwrap();
+    let execution = execute(&program).unwrap();
+    assert_eq!(execution.history["a"], vec![0, 2]);
+    assert_eq!(execution.history["b"], vec![0]);
+
+    let program = parse_program("big inc 9223372036854775807 if x == 0\nbig inc 1 if big > 0").unwrap();
+    assert_eq!(execute(&program), Err(String::from("register big overflows at instruction 2")));
+}
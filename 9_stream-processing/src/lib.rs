@@ -0,0 +1,86 @@
+extern crate util;
+
+use util::{ParseError, Solution};
+
+#[derive(Debug, Clone, Copy)]
+enum StreamState {
+    Normal,
+    Garbage,
+    Ignore
+}
+
+fn parse(stream : &str) -> (usize, usize) {
+    use StreamState::*;
+
+    let mut nest = 0;
+    let mut score = 0;
+    let mut garbage_count = 0;
+    let mut state = Normal;
+    for c in stream.chars() {
+        match (state, c) {
+            (Normal, '{')  => { nest += 1 }
+            (Normal, '}')  => { score += nest; nest -= 1 }
+            (Normal, '<')  => { state = Garbage }
+            (Garbage, '>') => { state = Normal }
+            (Garbage, '!') => { state = Ignore }
+            (Garbage, _)   => { garbage_count += 1 }
+            (Ignore, _)    => { state = Garbage }
+            (_, _)         => { }
+        }
+    }
+    (score, garbage_count)
+}
+
+/// Checks that the stream is made of balanced groups and terminated garbage.
+fn parse_input(input : &str) -> Result<String, ParseError> {
+    use StreamState::*;
+
+    let stream = input.trim_end();
+    let mut nest = 0;
+    let mut state = Normal;
+    for (i, c) in stream.char_indices() {
+        match (state, c) {
+            (Normal, '{')  => { nest += 1 }
+            (Normal, '}') if nest > 0 => { nest -= 1 }
+            (Normal, '}')  => return Err(ParseError::at(input, &stream[i..], "'{' before '}'")),
+            (Normal, '<')  => { state = Garbage }
+            (Normal, ',')  => { }
+            (Normal, _)    => return Err(ParseError::at(input, &stream[i..], "'{', '}', ',' or '<'")),
+            (Garbage, '>') => { state = Normal }
+            (Garbage, '!') => { state = Ignore }
+            (Garbage, _)   => { }
+            (Ignore, _)    => { state = Garbage }
+        }
+    }
+    match state {
+        Normal if nest == 0 => Ok(String::from(stream)),
+        Normal => Err(ParseError::at_end(stream, "'}'")),
+        _ => Err(ParseError::at_end(stream, "'>'")),
+    }
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    const DAY : u32 = 9;
+
+    type Input = String;
+    type Answer1 = usize;
+    type Answer2 = usize;
+    const LABEL1 : &'static str = "score";
+    const LABEL2 : &'static str = "garbage";
+
+    fn parse(input : &str) -> Result<String, String> { Ok(parse_input(input)?) }
+
+    fn part1(stream : &String) -> Result<usize, String> { Ok(parse(stream).0) }
+
+    fn part2(stream : &String) -> Result<usize, String> { Ok(parse(stream).1) }
+}
+
+#[test]
+fn test_parse_error() {
+    let e = parse_input("{{<a>},{x}}").unwrap_err();
+    assert_eq!((e.line, e.column), (1, 9));
+    assert_eq!(parse_input("{{<!>}").unwrap_err().expected, "'>'");
+    assert_eq!(parse_input("{{<a>}").unwrap_err().expected, "'}'");
+}
@@ -0,0 +1,65 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The system allocator, keeping track of the number of bytes allocated and
+/// the peak since the last call to `reset_peak`.
+pub struct Counting;
+
+static ALLOCATED : AtomicUsize = AtomicUsize::new(0);
+static PEAK : AtomicUsize = AtomicUsize::new(0);
+
+fn add(size : usize) {
+    let allocated = ALLOCATED.fetch_add(size, Ordering::SeqCst) + size;
+    PEAK.fetch_max(allocated, Ordering::SeqCst);
+}
+
+fn sub(size : usize) {
+    ALLOCATED.fetch_sub(size, Ordering::SeqCst);
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout : Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if ! ptr.is_null() {
+            add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout : Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if ! ptr.is_null() {
+            add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr : *mut u8, layout : Layout) {
+        System.dealloc(ptr, layout);
+        sub(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr : *mut u8, layout : Layout, new_size : usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if ! new_ptr.is_null() {
+            sub(layout.size());
+            add(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// Number of bytes currently allocated.
+pub fn allocated() -> usize {
+    ALLOCATED.load(Ordering::SeqCst)
+}
+
+/// Resets the peak to the number of bytes currently allocated.
+pub fn reset_peak() {
+    PEAK.store(allocated(), Ordering::SeqCst);
+}
+
+/// Highest number of bytes allocated at once since the last `reset_peak`.
+pub fn peak() -> usize {
+    PEAK.load(Ordering::SeqCst)
+}
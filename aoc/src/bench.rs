@@ -0,0 +1,112 @@
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+use util::Solution;
+use vm::Configurable;
+
+use alloc;
+use days;
+use millis;
+
+/// Timings and peak memory use of one stage of a solution over several runs.
+pub struct Stats {
+    pub min : Duration,
+    pub median : Duration,
+    pub max : Duration,
+    /// Highest number of bytes allocated by the stage on top of what was
+    /// already allocated when it started.
+    pub peak_bytes : usize,
+}
+
+/// Runs `stage` `runs` times, returning its statistics and the result of the
+/// last run.
+fn measure<T, F>(runs : usize, mut stage : F) -> Result<(Stats, T), String> where F : FnMut() -> Result<T, String> {
+    let mut times = Vec::with_capacity(runs);
+    let mut peak_bytes = 0;
+    let mut result = None;
+    for _ in 0..runs {
+        // drop the previous result first, so it isn't counted against this run
+        drop(result.take());
+        let base = alloc::allocated();
+        alloc::reset_peak();
+        let start = Instant::now();
+        let value = stage()?;
+        times.push(start.elapsed());
+        peak_bytes = usize::max(peak_bytes, alloc::peak() - base);
+        result = Some(value);
+    }
+    times.sort();
+    let stats = Stats {
+        min: times[0],
+        median: times[times.len() / 2],
+        max: times[times.len() - 1],
+        peak_bytes,
+    };
+    Ok((stats, result.unwrap()))
+}
+
+fn measure_part<I, A, F>(runs : usize, input : &I, part : F) -> Result<Stats, String>
+    where A : Display, F : Fn(&I) -> Result<A, String>
+{
+    measure(runs, || part(input)).map(|(stats, _)| stats)
+}
+
+/// Benchmarks the parsing stage and both parts of a solution. `runs` must be at
+/// least one.
+pub struct Bench<'a> {
+    pub input : &'a str,
+    pub runs : usize,
+    pub config : vm::Config,
+}
+
+impl<'a> Bench<'a> {
+    fn run<S : Solution, P : Fn(&str) -> Result<S::Input, String>>(self, parse : P) -> Result<Report, String> {
+        let runs = self.runs;
+        let (parse, input) = measure(runs, || parse(self.input))?;
+        Ok(vec![
+            ("parse", Ok(parse)),
+            ("part1", measure_part(runs, &input, S::part1)),
+            ("part2", measure_part(runs, &input, S::part2)),
+        ])
+    }
+}
+
+/// Results for the parsing stage and each part, which may have failed.
+pub type Report = Vec<(&'static str, Result<Stats, String>)>;
+
+impl<'a> days::Visitor for Bench<'a> {
+    type Output = Result<Report, String>;
+
+    fn visit<S : Solution>(self) -> Result<Report, String> {
+        self.run::<S, _>(S::parse)
+    }
+
+    fn visit_vm<S : Configurable>(self) -> Result<Report, String> {
+        let config = self.config;
+        self.run::<S, _>(|input| S::parse_with(input, config))
+    }
+}
+
+/// Header of the CSV rows printed by `print_report`.
+pub const CSV_HEADER : &str = "day,stage,runs,min_ms,median_ms,max_ms,peak_bytes";
+
+/// Prints a benchmark report as an aligned table, or as CSV rows without a
+/// header, so that runs can be appended to the same file.
+pub fn print_report(day : u32, runs : usize, report : &Report, csv : bool) {
+    if ! csv {
+        println!("{:>3}  {:<5}  {:>6}  {:>12}  {:>12}  {:>12}  {:>12}", "day", "stage", "runs", "min (ms)", "median (ms)", "max (ms)", "peak bytes");
+    }
+    for &(stage, ref stats) in report {
+        match *stats {
+            Ok(ref stats) if csv => println!("{},{},{},{:.3},{:.3},{:.3},{}", day, stage, runs,
+                                             millis(stats.min), millis(stats.median), millis(stats.max), stats.peak_bytes),
+            Ok(ref stats) => println!("{:>3}  {:<5}  {:>6}  {:>12.3}  {:>12.3}  {:>12.3}  {:>12}", day, stage, runs,
+                                      millis(stats.min), millis(stats.median), millis(stats.max), stats.peak_bytes),
+            Err(ref e) if csv => {
+                println!("{},{},{},,,,", day, stage, runs);
+                eprintln!("{}: {}", stage, e);
+            },
+            Err(ref e) => println!("{:>3}  {:<5}  {:>6}  {}", day, stage, runs, e),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use util::Solution;
+use vm::Configurable;
+
+/// An operation on a solution whose type is only known at runtime.
+pub trait Visitor {
+    type Output;
+    fn visit<S : Solution>(self) -> Self::Output;
+    /// Like `visit`, for the days whose input is an assembly program, which
+    /// runs with the `vm::Config` given in the options.
+    fn visit_vm<S : Configurable>(self) -> Self::Output;
+}
+
+macro_rules! days {
+    ($($day : expr => $solution : path),* ; vm: $($vm_day : expr => $vm_solution : path),* $(,)*) => {
+        /// Calls `visitor` with the solution for `day`, or returns `None` if there is no such day.
+        pub fn visit<V : Visitor>(day : u32, visitor : V) -> Option<V::Output> {
+            match day {
+                $($day => Some(visitor.visit::<$solution>()),)*
+                $($vm_day => Some(visitor.visit_vm::<$vm_solution>()),)*
+                _ => None,
+            }
+        }
+    }
+}
+
+days! {
+    1 => inverse_captcha::Puzzle,
+    2 => corruption_checksum::Puzzle,
+    3 => spiral_memory::Puzzle,
+    4 => high_entropy_passphrases::Puzzle,
+    5 => maze_of_trampolines::Puzzle,
+    6 => memory_reallocation::Puzzle,
+    7 => recursive_circus::Puzzle,
+    8 => registers::Puzzle,
+    9 => stream_processing::Puzzle,
+    10 => knot_hash::Puzzle,
+    11 => hex_ed::Puzzle,
+    12 => digital_plumber::Puzzle,
+    13 => packet_scanners::Puzzle,
+    14 => disk_defragmentation::Puzzle,
+    15 => dueling_generators::Puzzle,
+    16 => permutation_promenade::Puzzle,
+    17 => spinlock::Puzzle,
+    19 => series_of_tubes::Puzzle,
+    20 => particle_swarm::Puzzle,
+    21 => fractal_art::Puzzle,
+    22 => sporifica_virus::Puzzle,
+    24 => electromagnetic_moat::Puzzle,
+    25 => halting_problem::Puzzle;
+  vm:
+    18 => duet::Puzzle,
+    23 => coprocessor_conflation::Puzzle,
+}
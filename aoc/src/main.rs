@@ -0,0 +1,303 @@
+extern crate clap;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate toml;
+extern crate util;
+extern crate vm;
+
+extern crate inverse_captcha;
+extern crate corruption_checksum;
+extern crate spiral_memory;
+extern crate high_entropy_passphrases;
+extern crate maze_of_trampolines;
+extern crate memory_reallocation;
+extern crate recursive_circus;
+extern crate registers;
+extern crate stream_processing;
+extern crate knot_hash;
+extern crate hex_ed;
+extern crate digital_plumber;
+extern crate packet_scanners;
+extern crate disk_defragmentation;
+extern crate dueling_generators;
+extern crate permutation_promenade;
+extern crate spinlock;
+extern crate duet;
+extern crate series_of_tubes;
+extern crate particle_swarm;
+extern crate fractal_art;
+extern crate sporifica_virus;
+extern crate coprocessor_conflation;
+extern crate electromagnetic_moat;
+extern crate halting_problem;
+
+mod alloc;
+mod bench;
+mod days;
+mod verify;
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::{Arg, ArgMatches};
+use util::Solution;
+use vm::Configurable;
+
+#[global_allocator]
+static ALLOCATOR : alloc::Counting = alloc::Counting;
+
+fn millis(duration : Duration) -> f64 {
+    duration.as_secs() as f64 * 1000.0 + f64::from(duration.subsec_nanos()) / 1_000_000.0
+}
+
+/// Solves a single part of a puzzle.
+struct Run<'a> {
+    input : &'a str,
+    part2 : bool,
+    config : vm::Config,
+}
+
+impl<'a> Run<'a> {
+    fn run<S : Solution, P : FnOnce(&str) -> Result<S::Input, String>>(self, parse : P) -> Result<String, String> {
+        let input = parse(self.input)?;
+        if self.part2 {
+            S::part2(&input).map(|answer| answer.to_string())
+        } else {
+            S::part1(&input).map(|answer| answer.to_string())
+        }
+    }
+}
+
+impl<'a> days::Visitor for Run<'a> {
+    type Output = Result<String, String>;
+
+    fn visit<S : Solution>(self) -> Result<String, String> {
+        self.run::<S, _>(S::parse)
+    }
+
+    fn visit_vm<S : Configurable>(self) -> Result<String, String> {
+        let config = self.config;
+        self.run::<S, _>(|input| S::parse_with(input, config))
+    }
+}
+
+/// Solves the selected parts of a puzzle, reporting the time taken and any warnings.
+struct Solve<'a> {
+    input : &'a str,
+    parts : util::Parts,
+    config : vm::Config,
+}
+
+impl<'a> days::Visitor for Solve<'a> {
+    type Output = Vec<util::Report>;
+
+    fn visit<S : Solution>(self) -> Vec<util::Report> {
+        util::solve::<S>(self.input, self.parts)
+    }
+
+    fn visit_vm<S : Configurable>(self) -> Vec<util::Report> {
+        let config = self.config;
+        util::solve_with::<S, _>(self.input, self.parts, |input| S::parse_with(input, config))
+    }
+}
+
+fn max_steps_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("max-steps")
+        .long("max-steps")
+        .value_name("N")
+        .help("Gives up on the assembly programs of days 18 and 23 after N instructions")
+}
+
+fn detect_loops_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("detect-loops")
+        .long("detect-loops")
+        .help("Stops the assembly programs of days 18 and 23 if they are provably in an infinite loop")
+}
+
+fn arithmetic_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("arithmetic")
+        .long("arithmetic")
+        .value_name("MODE")
+        .possible_values(&["checked", "wrapping", "saturating"])
+        .help("Stops the assembly programs of days 18 and 23 on overflow (checked, the default), or wraps or saturates")
+}
+
+/// The config of the assembly programs: their limits from `--max-steps` and
+/// `--detect-loops`, and their arithmetic from `--arithmetic`.
+fn vm_config(options : &ArgMatches) -> vm::Config {
+    let max_steps = match options.value_of("max-steps").map(vm::watchdog::parse_max_steps) {
+        Some(Ok(steps)) => Some(steps),
+        Some(Err(e)) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        },
+        None => None,
+    };
+    vm::Config {
+        arithmetic: options.value_of("arithmetic").map(|mode| vm::Arithmetic::parse(mode).unwrap()).unwrap_or_default(),
+        limits: vm::Limits { max_steps, detect_loops: options.is_present("detect-loops") },
+    }
+}
+
+fn main() {
+    use clap::{App, AppSettings, SubCommand};
+    let options = App::new("Advent of Code 2017")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(SubCommand::with_name("run")
+                    .about("Runs the solution for a single day")
+                    .arg(Arg::with_name("day")
+                         .required(true)
+                         .help("Day of the puzzle (1-25)")
+                        )
+                    .arg(Arg::with_name("part2")
+                         .long("part2")
+                         .help("Solves part two instead of part one")
+                        )
+                    .arg(Arg::with_name("input")
+                         .long("input")
+                         .value_name("FILE")
+                         .help("Reads the puzzle input from FILE")
+                        )
+                    .arg(Arg::with_name("input-str")
+                         .long("input-str")
+                         .value_name("STRING")
+                         .conflicts_with("input")
+                         .help("Uses STRING as the puzzle input")
+                        )
+                    .arg(Arg::with_name("format")
+                         .long("format")
+                         .value_name("FORMAT")
+                         .possible_values(&["text", "json"])
+                         .default_value("text")
+                         .help("Prints the bare answer, or a JSON object with the answer, time taken and warnings")
+                        )
+                    .arg(Arg::with_name("bench")
+                         .long("bench")
+                         .value_name("N")
+                         .help("Times parsing and both parts over N runs instead of printing the answer")
+                        )
+                    .arg(Arg::with_name("timeout")
+                         .long("timeout")
+                         .value_name("SECONDS")
+                         .help("Stops long-running simulations after SECONDS, reporting the partial result")
+                        )
+                    .arg(max_steps_arg())
+                    .arg(detect_loops_arg())
+                    .arg(arithmetic_arg())
+                    .arg(Arg::with_name("csv")
+                         .long("csv")
+                         .requires("bench")
+                         .help("Prints the benchmark results as CSV rows")
+                        )
+                    .arg(Arg::with_name("csv-header")
+                         .long("csv-header")
+                         .requires("csv")
+                         .help("Prints the header row before the CSV rows")
+                        )
+                    .after_help("Without --input or --input-str, the input is read from stdin if it is piped, \
+                                otherwise from inputs/dayNN.txt in the workspace root if it exists.")
+                   )
+        .subcommand(SubCommand::with_name("verify")
+                    .about("Checks the solutions against a file of expected answers")
+                    .arg(Arg::with_name("answers")
+                         .long("answers")
+                         .value_name("FILE")
+                         .help("Reads the expected answers from FILE (default: answers.toml in the workspace root)")
+                        )
+                    .arg(max_steps_arg())
+                    .arg(detect_loops_arg())
+                    .arg(arithmetic_arg())
+                   )
+        .get_matches();
+
+    if let Some(options) = options.subcommand_matches("run") {
+        let day : u32 = match options.value_of("day").unwrap().parse() {
+            Ok(day) => day,
+            Err(_) => {
+                eprintln!("invalid day: '{}'", options.value_of("day").unwrap());
+                std::process::exit(1);
+            }
+        };
+        let config = vm_config(options);
+        if let Some(timeout) = options.value_of("timeout") {
+            match util::progress::parse_timeout(timeout) {
+                Ok(timeout) => util::progress::set_timeout(Some(timeout)),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        let source = util::InputSource::new(options.value_of("input"), options.value_of("input-str"), day);
+        let input = match source.read() {
+            Ok(input) => input,
+            Err(e) => {
+                eprintln!("error reading input: {}", e);
+                std::process::exit(1);
+            }
+        };
+        if let Some(runs) = options.value_of("bench") {
+            let runs = match runs.parse() {
+                Ok(runs) if runs > 0 => runs,
+                _ => {
+                    eprintln!("invalid number of runs: '{}'", runs);
+                    std::process::exit(1);
+                }
+            };
+            if options.is_present("csv-header") {
+                println!("{}", bench::CSV_HEADER);
+            }
+            match days::visit(day, bench::Bench { input: &input, runs, config }) {
+                Some(Ok(report)) => bench::print_report(day, runs, &report, options.is_present("csv")),
+                Some(Err(e)) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                },
+                None => {
+                    eprintln!("no solution for day {}", day);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        if options.value_of("format") == Some("json") {
+            let parts = if options.is_present("part2") { util::Parts::Two } else { util::Parts::One };
+            match days::visit(day, Solve { input: &input, parts, config }) {
+                Some(reports) => for report in reports {
+                    println!("{}", report.to_json());
+                },
+                None => {
+                    eprintln!("no solution for day {}", day);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        let run = Run { input: &input, part2: options.is_present("part2"), config };
+        match days::visit(day, run) {
+            Some(Ok(answer)) => println!("{}", answer),
+            Some(Err(e)) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            },
+            None => {
+                eprintln!("no solution for day {}", day);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if let Some(options) = options.subcommand_matches("verify") {
+        let config = vm_config(options);
+        let path = options.value_of("answers").map(PathBuf::from).unwrap_or_else(verify::default_answers_path);
+        match verify::verify(&path, config) {
+            Ok(true) => { },
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use toml;
+
+use days;
+use millis;
+use Run;
+
+/// The contents of an answers file: a list of `[[answer]]` tables.
+#[derive(Debug, Deserialize)]
+struct Answers {
+    #[serde(default)]
+    answer : Vec<Expected>,
+}
+
+/// The expected answer to one part of a puzzle for a given input.
+#[derive(Debug, Deserialize)]
+struct Expected {
+    day : u32,
+    part : u32,
+    /// Path of the input, relative to the answers file. Defaults to the day's
+    /// default input file.
+    input : Option<String>,
+    /// Either a string or an integer.
+    expected : toml::Value,
+}
+
+enum Outcome {
+    Pass,
+    Mismatch(String),
+    Fail(String),
+}
+
+impl Outcome {
+    fn passed(&self) -> bool {
+        matches!(*self, Outcome::Pass)
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Outcome::Pass => write!(f, "pass"),
+            Outcome::Mismatch(ref answer) => write!(f, "mismatch (got {})", answer),
+            Outcome::Fail(ref e) => write!(f, "fail ({})", e),
+        }
+    }
+}
+
+fn read_answers(path : &Path) -> Result<Answers, String> {
+    let mut contents = String::new();
+    File::open(path).and_then(|mut file| file.read_to_string(&mut contents))
+        .map_err(|e| format!("error reading {}: {}", path.display(), e))?;
+    toml::from_str(&contents).map_err(|e| format!("error parsing {}: {}", path.display(), e))
+}
+
+fn expected_string(value : &toml::Value) -> Result<String, String> {
+    match *value {
+        toml::Value::String(ref s) => Ok(s.clone()),
+        toml::Value::Integer(i) => Ok(i.to_string()),
+        ref value => Err(format!("expected answer must be a string or integer, not {}", value.type_str())),
+    }
+}
+
+fn check(expected : &Expected, dir : &Path, config : vm::Config) -> Outcome {
+    if expected.part != 1 && expected.part != 2 {
+        return Outcome::Fail(format!("invalid part {}", expected.part));
+    }
+    let answer = match expected_string(&expected.expected) {
+        Ok(answer) => answer,
+        Err(e) => return Outcome::Fail(e),
+    };
+    let path = match expected.input {
+        Some(ref input) => dir.join(input),
+        None => util::default_input_path(expected.day),
+    };
+    let input = match util::InputSource::File(path.clone()).read() {
+        Ok(input) => input,
+        Err(e) => return Outcome::Fail(format!("error reading {}: {}", path.display(), e)),
+    };
+    match days::visit(expected.day, Run { input: &input, part2: expected.part == 2, config }) {
+        Some(Ok(ref result)) if *result == answer => Outcome::Pass,
+        Some(Ok(result)) => Outcome::Mismatch(result),
+        Some(Err(e)) => Outcome::Fail(e),
+        None => Outcome::Fail(format!("no solution for day {}", expected.day)),
+    }
+}
+
+/// Default location of the answers file.
+pub fn default_answers_path() -> PathBuf {
+    util::workspace_root().join("answers.toml")
+}
+
+/// Checks every answer in the answers file at `path`, printing one line per
+/// answer. Returns whether all of them passed.
+pub fn verify(path : &Path, config : vm::Config) -> Result<bool, String> {
+    let answers = read_answers(path)?;
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let mut failures = 0;
+    let mut total = Duration::new(0, 0);
+    for expected in &answers.answer {
+        let start = Instant::now();
+        let outcome = check(expected, dir, config);
+        let elapsed = start.elapsed();
+        total += elapsed;
+        println!("day {:2} part {}: {:>10.2}ms  {}", expected.day, expected.part, millis(elapsed), outcome);
+        if ! outcome.passed() {
+            failures += 1;
+        }
+    }
+    println!("{} passed, {} failed in {:.2}ms", answers.answer.len() - failures, failures, millis(total));
+    Ok(failures == 0)
+}
+
+#[test]
+fn test_parse_answers() {
+    let answers : Answers = toml::from_str(r#"
+        [[answer]]
+        day = 1
+        part = 1
+        input = "inputs/day01.txt"
+        expected = 3
+
+        [[answer]]
+        day = 10
+        part = 2
+        expected = "a2582a3a0e66e6e86e3812dcb672a272"
+    "#).unwrap();
+    assert_eq!(answers.answer.len(), 2);
+    assert_eq!(expected_string(&answers.answer[0].expected), Ok(String::from("3")));
+    assert_eq!(expected_string(&answers.answer[1].expected), Ok(String::from("a2582a3a0e66e6e86e3812dcb672a272")));
+    assert!(answers.answer[1].input.is_none());
+}
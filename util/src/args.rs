@@ -0,0 +1,62 @@
+//! Options in the program arguments, for the binaries that don't parse them
+//! with clap.
+
+use std::env;
+use std::process;
+
+fn args() -> Vec<String> {
+    env::args().skip(1).collect()
+}
+
+/// The value of the last `--name VALUE` (or `--name=VALUE`) in `args`, if any.
+fn find_value<S : AsRef<str>>(args : &[S], name : &str) -> Result<Option<String>, String> {
+    let prefix = format!("{}=", name);
+    let mut value = None;
+    for (i, arg) in args.iter().enumerate() {
+        let arg = arg.as_ref();
+        if arg == name {
+            match args.get(i + 1) {
+                Some(next) => value = Some(next.as_ref().to_string()),
+                None => return Err(format!("missing value for {}", name)),
+            }
+        } else if let Some(v) = arg.strip_prefix(&prefix) {
+            value = Some(v.to_string());
+        }
+    }
+    Ok(value)
+}
+
+/// The value given with `name` (like `--format`) in the program arguments, if
+/// any, or an error if the option is the last argument and has no value.
+pub fn value_of(name : &str) -> Result<Option<String>, String> {
+    find_value(&args(), name)
+}
+
+/// The value given with `name`, parsed with `parse`.
+pub fn parse_value_of<T, F>(name : &str, parse : F) -> Result<Option<T>, String> where F : FnOnce(&str) -> Result<T, String> {
+    value_of(name)?.map(|value| parse(&value)).transpose()
+}
+
+/// True if the flag `name` (like `--debug`) is in the program arguments.
+pub fn is_present(name : &str) -> bool {
+    args().iter().any(|arg| arg == name)
+}
+
+/// The value, or exits with status 1 after printing the error.
+pub fn or_exit<T>(result : Result<T, String>) -> T {
+    result.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1)
+    })
+}
+
+#[test]
+fn test_find_value() {
+    let args = |s : &str| s.split(' ').map(String::from).collect::<Vec<_>>();
+    assert_eq!(find_value(&args("--part2 --format json"), "--format"), Ok(Some(String::from("json"))));
+    assert_eq!(find_value(&args("--format=text --format json"), "--format"), Ok(Some(String::from("json"))));
+    assert_eq!(find_value(&args("--formats json"), "--format"), Ok(None));
+    assert_eq!(find_value(&args("--input-str=1122 --part1"), "--input"), Ok(None));
+    assert_eq!(find_value(&args("--input-str=1122 --part1"), "--input-str"), Ok(Some(String::from("1122"))));
+    assert_eq!(find_value(&args("--part1 --format"), "--format"), Err(String::from("missing value for --format")));
+}
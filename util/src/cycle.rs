@@ -0,0 +1,93 @@
+//! Cycle detection for sequences of states `x0, f(x0), f(f(x0)), ...`, where
+//! `step` advances a state in place.
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::hash::Hash;
+
+/// Where a sequence of states starts repeating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    /// Index of the first state in the cycle (mu).
+    pub start : usize,
+    /// Number of states in the cycle (lambda).
+    pub length : usize,
+}
+
+impl Cycle {
+    /// The index of the earliest state equal to state `n`, so a long process
+    /// can be skipped to its end by running only that many steps.
+    pub fn index(&self, n : usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+}
+
+/// Finds the cycle using Brent's algorithm, which only keeps two states in
+/// memory but runs `step` about three times as often as `hashed`.
+pub fn brent<T, F>(start : T, mut step : F) -> Cycle
+    where T : Clone + PartialEq, F : FnMut(&mut T)
+{
+    // find the length by moving the tortoise to the hare at every power of two
+    let mut tortoise = start.clone();
+    let mut hare = start.clone();
+    step(&mut hare);
+    let mut power = 1;
+    let mut length = 1;
+    while tortoise != hare {
+        if length == power {
+            tortoise.clone_from(&hare);
+            power *= 2;
+            length = 0;
+        }
+        step(&mut hare);
+        length += 1;
+    }
+
+    // with the hare `length` steps ahead, they meet at the start of the cycle
+    let mut tortoise = start.clone();
+    let mut hare = start;
+    for _ in 0..length {
+        step(&mut hare);
+    }
+    let mut mu = 0;
+    while tortoise != hare {
+        step(&mut tortoise);
+        step(&mut hare);
+        mu += 1;
+    }
+    Cycle { start: mu, length }
+}
+
+/// Finds the cycle by remembering every state, returning it along with the
+/// first repeated state.
+pub fn hashed<T, F>(start : T, mut step : F) -> (Cycle, T)
+    where T : Clone + Eq + Hash, F : FnMut(&mut T)
+{
+    let mut seen = HashMap::new();
+    let mut state = start;
+    let mut count = 0;
+    loop {
+        match seen.entry(state.clone()) {
+            Entry::Occupied(entry) => return (Cycle { start: *entry.get(), length: count - entry.get() }, state),
+            Entry::Vacant(entry) => { entry.insert(count); },
+        }
+        step(&mut state);
+        count += 1;
+    }
+}
+
+#[test]
+fn test_cycle() {
+    // 7, 3, 4, 0, 1, 2, 3, ...
+    let step = |x : &mut u32| *x = (*x + 1) % 5;
+    let cycle = Cycle { start: 1, length: 5 };
+    assert_eq!(brent(7, step), cycle);
+    assert_eq!(hashed(7, step), (cycle, 3));
+    assert_eq!(brent(0, step), Cycle { start: 0, length: 5 });
+    assert_eq!(cycle.index(0), 0);
+    assert_eq!(cycle.index(13), 3);
+}
@@ -0,0 +1,128 @@
+use std::cell::RefCell;
+use std::fmt::Write;
+use std::time::Duration;
+
+use args;
+
+/// How answers are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// One labelled line per part.
+    Text,
+    /// One JSON object per line and part.
+    Json,
+}
+
+impl Format {
+    /// Text, unless `--format json` was given.
+    pub fn from_args() -> Result<Format, String> {
+        Ok(args::parse_value_of("--format", Format::parse)?.unwrap_or(Format::Text))
+    }
+
+    pub fn parse(name : &str) -> Result<Format, String> {
+        match name {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("invalid format: '{}' (expected text or json)", name)),
+        }
+    }
+}
+
+thread_local! {
+    static WARNINGS : RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+/// Reports a problem that doesn't stop a solution, like ignored input. The
+/// warning is collected if called from `collect_warnings`, and printed to stderr
+/// otherwise.
+pub fn warn<S : Into<String>>(message : S) {
+    let message = message.into();
+    WARNINGS.with(|warnings| match *warnings.borrow_mut() {
+        Some(ref mut warnings) => warnings.push(message),
+        None => eprintln!("warning: {}", message),
+    });
+}
+
+/// Calls `f`, returning its result along with any warnings it reported.
+pub fn collect_warnings<T, F>(f : F) -> (T, Vec<String>) where F : FnOnce() -> T {
+    let outer = WARNINGS.with(|warnings| warnings.borrow_mut().replace(Vec::new()));
+    let result = f();
+    let collected = WARNINGS.with(|warnings| warnings.replace(outer)).unwrap_or_default();
+    (result, collected)
+}
+
+/// The outcome of solving one part of a puzzle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub day : u32,
+    pub part : u32,
+    /// The answer, or the reason there isn't one.
+    pub answer : Result<String, String>,
+    /// Time spent solving the part, not counting parsing.
+    pub elapsed : Duration,
+    pub warnings : Vec<String>,
+}
+
+fn json_string(s : &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+impl Report {
+    /// Formats the report as a JSON object with the fields `day`, `part`,
+    /// `answer` (null if there was an error), `elapsed_ms` and `warnings`
+    /// (including the error, if any).
+    pub fn to_json(&self) -> String {
+        let answer = match self.answer {
+            Ok(ref answer) => json_string(answer),
+            Err(_) => String::from("null"),
+        };
+        let mut warnings : Vec<String> = self.warnings.iter().map(|w| json_string(w)).collect();
+        if let Err(ref e) = self.answer {
+            warnings.push(json_string(&format!("error: {}", e)));
+        }
+        let elapsed_ms = self.elapsed.as_secs() as f64 * 1000.0 + f64::from(self.elapsed.subsec_nanos()) / 1_000_000.0;
+        format!("{{\"day\":{},\"part\":{},\"answer\":{},\"elapsed_ms\":{:.3},\"warnings\":[{}]}}",
+                self.day, self.part, answer, elapsed_ms, warnings.join(","))
+    }
+}
+
+#[test]
+fn test_to_json() {
+    let report = Report {
+        day: 6,
+        part: 2,
+        answer: Ok(String::from("1610")),
+        elapsed: Duration::from_millis(12),
+        warnings: vec![String::from("unconsumed: \"x\"")],
+    };
+    assert_eq!(report.to_json(), r#"{"day":6,"part":2,"answer":"1610","elapsed_ms":12.000,"warnings":["unconsumed: \"x\""]}"#);
+    let report = Report { answer: Err(String::from("invalid input")), warnings: vec![], ..report };
+    assert_eq!(report.to_json(), r#"{"day":6,"part":2,"answer":null,"elapsed_ms":12.000,"warnings":["error: invalid input"]}"#);
+}
+
+#[test]
+fn test_collect_warnings() {
+    let (result, warnings) = collect_warnings(|| {
+        warn("first");
+        let ((), inner) = collect_warnings(|| warn("inner"));
+        assert_eq!(inner, vec![String::from("inner")]);
+        warn(String::from("second"));
+        5
+    });
+    assert_eq!(result, 5);
+    assert_eq!(warnings, vec![String::from("first"), String::from("second")]);
+}
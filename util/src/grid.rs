@@ -0,0 +1,254 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use parse::ParseError;
+
+/// A rectangular grid of cells stored in row-major order. Positions are
+/// `(x, y)` pairs, with `x` the column and `y` the row counted from the top.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width : usize,
+    height : usize,
+    cells : Vec<T>,
+}
+
+impl<T : Clone> Grid<T> {
+    /// Creates a grid with every cell set to `fill`.
+    pub fn new(width : usize, height : usize, fill : T) -> Grid<T> {
+        Grid { width, height, cells: vec![fill; width * height] }
+    }
+
+    /// Adds `left` columns to the left, `right` columns to the right, `top` rows
+    /// above and `bottom` rows below the grid, filled with `fill`.
+    pub fn grow(&mut self, left : usize, right : usize, top : usize, bottom : usize, fill : T) {
+        let width = left + self.width + right;
+        let height = top + self.height + bottom;
+        let mut cells = Vec::with_capacity(width * height);
+        cells.resize(top * width, fill.clone());
+        for row in self.cells.chunks(usize::max(self.width, 1)).take(self.height) {
+            cells.extend(::std::iter::repeat_n(fill.clone(), left));
+            cells.extend_from_slice(row);
+            cells.extend(::std::iter::repeat_n(fill.clone(), right));
+        }
+        cells.resize(width * height, fill);
+        *self = Grid { width, height, cells };
+    }
+
+    /// Copies the `width` x `height` block with its top left corner at `(x, y)`.
+    pub fn subgrid(&self, x : usize, y : usize, width : usize, height : usize) -> Grid<T> {
+        assert!(x + width <= self.width && y + height <= self.height, "subgrid out of bounds");
+        let mut cells = Vec::with_capacity(width * height);
+        for row in y..(y + height) {
+            cells.extend_from_slice(&self.row(row)[x..(x + width)]);
+        }
+        Grid { width, height, cells }
+    }
+
+    /// Copies `other` into the grid with its top left corner at `(x, y)`.
+    pub fn paste(&mut self, x : usize, y : usize, other : &Grid<T>) {
+        assert!(x + other.width <= self.width && y + other.height <= self.height, "paste out of bounds");
+        for (i, row) in other.rows().enumerate() {
+            self.row_mut(y + i)[x..(x + other.width)].clone_from_slice(row);
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    /// Creates a grid from cells in row-major order.
+    pub fn from_vec(width : usize, height : usize, cells : Vec<T>) -> Grid<T> {
+        assert_eq!(width * height, cells.len(), "wrong number of cells for a {} x {} grid", width, height);
+        Grid { width, height, cells }
+    }
+
+    /// Parses a grid with one character per cell and one line per row, using
+    /// `cell` to convert each character (`None` for invalid characters, which
+    /// are reported as not being `expected`). Empty lines are skipped.
+    pub fn parse<F>(input : &str, expected : &str, cell : F) -> Result<Grid<T>, ParseError>
+        where F : Fn(char) -> Option<T>
+    {
+        let mut cells = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+        for line in input.lines().filter(|line| ! line.is_empty()) {
+            let mut count = 0;
+            for (i, c) in line.char_indices() {
+                if width == Some(count) {
+                    return Err(ParseError::at(input, &line[i..], "end of row"));
+                }
+                cells.push(cell(c).ok_or_else(|| ParseError::at(input, &line[i..], expected))?);
+                count += 1;
+            }
+            match width {
+                Some(width) if count < width => {
+                    return Err(ParseError::at(input, &line[line.len()..], format!("row of {} cells", width)));
+                },
+                _ => width = Some(count),
+            }
+            height += 1;
+        }
+        Ok(Grid { width: width.unwrap_or(0), height, cells })
+    }
+
+    pub fn width(&self) -> usize { self.width }
+
+    pub fn height(&self) -> usize { self.height }
+
+    /// The cells in row-major order.
+    pub fn cells(&self) -> &[T] { &self.cells }
+
+    pub fn cells_mut(&mut self) -> &mut [T] { &mut self.cells }
+
+    pub fn contains(&self, x : isize, y : isize) -> bool {
+        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+    }
+
+    /// The cell at `(x, y)`, or `None` if it is outside the grid.
+    pub fn get(&self, x : isize, y : isize) -> Option<&T> {
+        if self.contains(x, y) {
+            Some(&self.cells[y as usize * self.width + x as usize])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, x : isize, y : isize) -> Option<&mut T> {
+        if self.contains(x, y) {
+            Some(&mut self.cells[y as usize * self.width + x as usize])
+        } else {
+            None
+        }
+    }
+
+    /// The cell at `(x, y)`, without bounds checking.
+    ///
+    /// # Safety
+    ///
+    /// `(x, y)` must be inside the grid.
+    pub unsafe fn get_unchecked(&self, x : usize, y : usize) -> &T {
+        self.cells.get_unchecked(y * self.width + x)
+    }
+
+    /// The cell at `(x, y)`, without bounds checking.
+    ///
+    /// # Safety
+    ///
+    /// `(x, y)` must be inside the grid.
+    pub unsafe fn get_unchecked_mut(&mut self, x : usize, y : usize) -> &mut T {
+        self.cells.get_unchecked_mut(y * self.width + x)
+    }
+
+    pub fn row(&self, y : usize) -> &[T] {
+        &self.cells[(y * self.width)..((y + 1) * self.width)]
+    }
+
+    pub fn row_mut(&mut self, y : usize) -> &mut [T] {
+        &mut self.cells[(y * self.width)..((y + 1) * self.width)]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(usize::max(self.width, 1)).take(self.height)
+    }
+
+    pub fn column(&self, x : usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "column out of bounds");
+        self.cells[x..].iter().step_by(self.width)
+    }
+
+    /// Every position in the grid, in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    fn neighbors(&self, x : usize, y : usize, offsets : &'static [(isize, isize)]) -> impl Iterator<Item = (usize, usize)> {
+        let (width, height) = (self.width as isize, self.height as isize);
+        offsets.iter()
+            .map(move |&(dx, dy)| (x as isize + dx, y as isize + dy))
+            .filter(move |&(x, y)| x >= 0 && y >= 0 && x < width && y < height)
+            .map(|(x, y)| (x as usize, y as usize))
+    }
+
+    /// The positions above, left, right and below `(x, y)` that are inside the grid.
+    pub fn neighbors4(&self, x : usize, y : usize) -> impl Iterator<Item = (usize, usize)> {
+        const OFFSETS : [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+        self.neighbors(x, y, &OFFSETS)
+    }
+
+    /// The positions around `(x, y)`, including diagonals, that are inside the grid.
+    pub fn neighbors8(&self, x : usize, y : usize) -> impl Iterator<Item = (usize, usize)> {
+        const OFFSETS : [(isize, isize); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+        self.neighbors(x, y, &OFFSETS)
+    }
+
+    /// Renders the grid as text, one line per row, using `cell` to convert each cell.
+    pub fn render<F>(&self, cell : F) -> String where F : Fn(&T) -> char {
+        let mut text = String::with_capacity((self.width + 1) * self.height);
+        for row in self.rows() {
+            text.extend(row.iter().map(&cell));
+            text.push('\n');
+        }
+        text
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y) : (usize, usize)) -> &T {
+        assert!(x < self.width && y < self.height, "({}, {}) is outside the {} x {} grid", x, y, self.width, self.height);
+        &self.cells[y * self.width + x]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y) : (usize, usize)) -> &mut T {
+        assert!(x < self.width && y < self.height, "({}, {}) is outside the {} x {} grid", x, y, self.width, self.height);
+        &mut self.cells[y * self.width + x]
+    }
+}
+
+/// Grids of ASCII bytes display as text.
+impl fmt::Display for Grid<u8> {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.render(|&c| c as char))
+    }
+}
+
+#[test]
+fn test_parse_render() {
+    let input = "#..\n.#.\n";
+    let grid = Grid::parse(input, "'.' or '#'", |c| match c {
+        '.' => Some(false),
+        '#' => Some(true),
+        _ => None,
+    }).unwrap();
+    assert_eq!((grid.width(), grid.height()), (3, 2));
+    assert!(grid[(1, 1)] && ! grid[(1, 0)]);
+    assert_eq!(grid.get(-1, 0), None);
+    assert_eq!(grid.render(|&on| if on { '#' } else { '.' }), input);
+    assert_eq!(grid.column(1).cloned().collect::<Vec<_>>(), vec![false, true]);
+
+    let e = Grid::parse("#..\n.x.\n", "'.' or '#'", |c| if c == 'x' { None } else { Some(c) }).unwrap_err();
+    assert_eq!((e.line, e.column, e.expected.as_str()), (2, 2, "'.' or '#'"));
+    let e = Grid::parse("#..\n..\n", "cell", Some).unwrap_err();
+    assert_eq!((e.line, e.column, e.expected.as_str()), (2, 3, "row of 3 cells"));
+}
+
+#[test]
+fn test_grow_subgrid() {
+    let mut grid = Grid::from_vec(2, 2, b"ab\ncd".iter().cloned().filter(|&c| c != b'\n').collect());
+    grid.grow(1, 0, 0, 2, b'.');
+    assert_eq!(grid.to_string(), ".ab\n.cd\n...\n...\n");
+    assert_eq!(grid.subgrid(1, 0, 2, 2).to_string(), "ab\ncd\n");
+    let mut blank = Grid::new(3, 3, b' ');
+    blank.paste(1, 1, &grid.subgrid(1, 0, 2, 2));
+    assert_eq!(blank.to_string(), "   \n ab\n cd\n");
+}
+
+#[test]
+fn test_neighbors() {
+    let grid = Grid::new(3, 3, 0);
+    assert_eq!(grid.neighbors4(0, 0).collect::<Vec<_>>(), vec![(1, 0), (0, 1)]);
+    assert_eq!(grid.neighbors8(1, 1).count(), 8);
+    assert_eq!(grid.neighbors8(2, 2).collect::<Vec<_>>(), vec![(1, 1), (2, 1), (1, 2)]);
+}
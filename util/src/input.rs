@@ -0,0 +1,120 @@
+use std::env;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+use args;
+use repl::{self, Mode};
+use solution::Parts;
+
+/// Where a day's puzzle input comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    /// A file given with `--input FILE`, or the day's default input file.
+    File(PathBuf),
+    /// Input passed directly on the command line with `--input-str STRING`.
+    Str(String),
+    /// Standard input, either piped or entered interactively.
+    Stdin,
+}
+
+/// Root directory of the workspace, where `inputs/` and `answers.toml` are
+/// looked up: `AOC_ROOT` if it is set, otherwise the current directory if it
+/// has either of them, and otherwise the workspace the program was built in.
+pub fn workspace_root() -> PathBuf {
+    if let Some(root) = env::var_os("AOC_ROOT").filter(|root| ! root.is_empty()) {
+        return PathBuf::from(root);
+    }
+    if let Ok(dir) = env::current_dir() {
+        if dir.join("inputs").is_dir() || dir.join("answers.toml").is_file() {
+            return dir;
+        }
+    }
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap().to_path_buf()
+}
+
+/// Path of the default input file for `day`: `inputs/dayNN.txt` under the
+/// workspace root.
+pub fn default_input_path(day : u32) -> PathBuf {
+    workspace_root().join("inputs").join(format!("day{:02}.txt", day))
+}
+
+impl InputSource {
+    /// Picks the input source from an explicit file or inline string. Without
+    /// either, piped stdin is used if there is any, then the default input file
+    /// for `day` if it exists, and finally interactive stdin.
+    pub fn new(file : Option<&str>, inline : Option<&str>, day : u32) -> InputSource {
+        if let Some(file) = file {
+            return InputSource::File(PathBuf::from(file));
+        }
+        if let Some(inline) = inline {
+            return InputSource::Str(inline.to_string());
+        }
+        let default = default_input_path(day);
+        if ::is_tty() && default.is_file() {
+            InputSource::File(default)
+        } else {
+            InputSource::Stdin
+        }
+    }
+
+    /// Picks the input source from `--input FILE` or `--input-str STRING` in
+    /// the program arguments (see `new`).
+    pub fn from_args(day : u32) -> InputSource {
+        let file = args::or_exit(args::value_of("--input"));
+        let inline = args::or_exit(args::value_of("--input-str"));
+        InputSource::new(file.as_deref(), inline.as_deref(), day)
+    }
+
+    /// Reads the whole input. Interactive stdin is read up to the first blank line.
+    pub fn read(&self) -> io::Result<String> {
+        let mut input = String::new();
+        match *self {
+            InputSource::File(ref path) => { File::open(path)?.read_to_string(&mut input)?; },
+            InputSource::Str(ref s) => input.push_str(s),
+            InputSource::Stdin => { ::get_multiline(&mut input)?; },
+        }
+        Ok(input)
+    }
+
+    fn run<F>(&self, mode : Mode, prompt : &str, parts : Parts, mut run : F) where F : FnMut(&str, Parts) {
+        if *self == InputSource::Stdin && ::is_tty() {
+            return repl::run(mode, prompt, parts, run);
+        }
+        match self.read() {
+            Ok(input) => run(&input, parts),
+            Err(e) => eprintln!("error reading input: {}", e),
+        }
+    }
+
+    /// Runs `run` on the input with `parts`, or starts an interactive session
+    /// (see `repl::run`) where each line entered is an input if stdin is a tty.
+    pub fn run_lines<F>(&self, parts : Parts, run : F) where F : FnMut(&str, Parts) {
+        self.run(Mode::Lines, "", parts, run)
+    }
+
+    /// Runs `run` on the input with `parts`, or starts an interactive session
+    /// (see `repl::run`) where inputs are blocks of lines ended by a blank line
+    /// if stdin is a tty.
+    pub fn run_multiline<F>(&self, prompt : &str, parts : Parts, run : F) where F : FnMut(&str, Parts) {
+        self.run(Mode::Multiline, prompt, parts, run)
+    }
+}
+
+#[test]
+fn test_read() {
+    let source = InputSource::new(None, Some("65 8921"), 15);
+    assert_eq!(source, InputSource::Str(String::from("65 8921")));
+    assert_eq!(source.read().unwrap(), "65 8921");
+    assert!(default_input_path(3).ends_with("inputs/day03.txt"));
+}
+
+#[test]
+fn test_workspace_root() {
+    env::set_var("AOC_ROOT", "/srv/aoc");
+    assert_eq!(default_input_path(7), PathBuf::from("/srv/aoc/inputs/day07.txt"));
+    env::remove_var("AOC_ROOT");
+    // tests run in the crate's directory, which has neither inputs/ nor answers.toml
+    assert!(workspace_root().join("util").is_dir());
+}
@@ -0,0 +1,146 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use nom::{self, ErrorKind, IResult};
+
+/// An error in the puzzle input, with the position where it was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Line number, starting from 1.
+    pub line : usize,
+    /// Column number in characters, starting from 1.
+    pub column : usize,
+    /// The whole line containing the error.
+    pub snippet : String,
+    /// What was expected at the error position.
+    pub expected : String,
+}
+
+impl ParseError {
+    /// Creates an error at the start of `rest`, which must be a slice of `input`.
+    pub fn at<S : Into<String>>(input : &str, rest : &str, expected : S) -> ParseError {
+        let offset = rest.as_ptr() as usize - input.as_ptr() as usize;
+        assert!(offset <= input.len(), "error position is not part of the input");
+        let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[offset..].find('\n').map_or(input.len(), |i| offset + i);
+        ParseError {
+            line: input[..offset].matches('\n').count() + 1,
+            column: input[line_start..offset].chars().count() + 1,
+            snippet: input[line_start..line_end].trim_end_matches('\r').to_string(),
+            expected: expected.into(),
+        }
+    }
+
+    /// Creates an error at the end of `input`.
+    pub fn at_end<S : Into<String>>(input : &str, expected : S) -> ParseError {
+        ParseError::at(input, &input[input.len()..], expected)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "line {}, column {}: expected {}", self.line, self.column, self.expected)?;
+        writeln!(f, "    {}", self.snippet)?;
+        write!(f, "    {:>width$}", "^", width = self.column)
+    }
+}
+
+impl Error for ParseError {
+    fn description(&self) -> &str { "invalid puzzle input" }
+}
+
+impl From<ParseError> for String {
+    fn from(e : ParseError) -> String { e.to_string() }
+}
+
+fn describe(kind : &ErrorKind) -> String {
+    let expected = match *kind {
+        ErrorKind::Tag | ErrorKind::TagStr => "token",
+        ErrorKind::Char => "character",
+        ErrorKind::Digit => "digit",
+        ErrorKind::Alpha => "letter",
+        ErrorKind::AlphaNumeric => "letter or digit",
+        ErrorKind::MapRes | ErrorKind::MapOpt => "valid value",
+        ErrorKind::Verify => "value in range",
+        ErrorKind::Alt => "one of the alternatives",
+        ErrorKind::Switch => "known keyword",
+        ErrorKind::Eof => "end of input",
+        ErrorKind::RegexpMatch | ErrorKind::RegexpFind | ErrorKind::RegexpCapture => "matching token",
+        ref kind => return kind.description().to_lowercase(),
+    };
+    String::from(expected)
+}
+
+/// Finds the error position furthest into the input in a verbose nom error.
+fn furthest<'a, 'e>(error : &'e nom::Err<&'a str>) -> Option<(&'a str, &'e ErrorKind)> {
+    let (here, next) = match *error {
+        nom::Err::Code(_) => (None, None),
+        nom::Err::Node(_, ref next) => (None, Some(next)),
+        nom::Err::Position(ref kind, rest) => (Some((rest, kind)), None),
+        nom::Err::NodePosition(ref kind, rest, ref next) => (Some((rest, kind)), Some(next)),
+    };
+    next.into_iter().flat_map(|next| next.iter().filter_map(furthest))
+        .chain(here)
+        .min_by_key(|&(rest, _)| rest.len())
+}
+
+/// Converts the result of running a nom parser on `rest` (a slice of `input`)
+/// into a `ParseError`, requiring that everything but whitespace is consumed.
+pub fn complete<'a, O>(input : &'a str, rest : &'a str, result : IResult<&'a str, O>) -> Result<O, ParseError> {
+    match result {
+        IResult::Done(remaining, _) if ! remaining.trim().is_empty() => {
+            let remaining = remaining.trim_start();
+            Err(ParseError::at(input, remaining, "end of line"))
+        },
+        IResult::Done(_, output) => Ok(output),
+        IResult::Error(e) => Err(match furthest(&e) {
+            Some((position, kind)) => ParseError::at(input, position, describe(kind)),
+            None => ParseError::at(input, rest, describe(&e.into_error_kind())),
+        }),
+        IResult::Incomplete(_) => Err(ParseError::at(input, &rest[rest.len()..], "more input")),
+    }
+}
+
+/// Parses each non-blank line of `input` with `parser`.
+pub fn parse_lines<'a, O, F>(input : &'a str, parser : F) -> Result<Vec<O>, ParseError>
+    where F : Fn(&'a str) -> IResult<&'a str, O>
+{
+    input.lines()
+        .filter(|line| ! line.trim().is_empty())
+        .map(|line| complete(input, line, parser(line)))
+        .collect()
+}
+
+/// Parses `word` (a slice of `input`) with `str::parse`, describing what was
+/// expected with `expected`.
+pub fn parse_word<T : FromStr>(input : &str, word : &str, expected : &str) -> Result<T, ParseError> {
+    word.parse().map_err(|_| ParseError::at(input, word, expected))
+}
+
+/// Parses each whitespace-separated word of `text` (a slice of `input`) with
+/// `str::parse`.
+pub fn parse_words<T : FromStr>(input : &str, text : &str, expected : &str) -> Result<Vec<T>, ParseError> {
+    text.split_whitespace().map(|word| parse_word(input, word, expected)).collect()
+}
+
+#[test]
+fn test_position() {
+    let input = "1 2 3\n4 x 6\n";
+    let e = parse_words::<u32>(input, input, "number").unwrap_err();
+    assert_eq!(e, ParseError { line: 2, column: 3, snippet: String::from("4 x 6"), expected: String::from("number") });
+    assert_eq!(e.to_string(), "line 2, column 3: expected number\n    4 x 6\n      ^");
+    assert_eq!(ParseError::at_end(input, "more").line, 3);
+}
+
+#[test]
+fn test_nom() {
+    use nom::{alpha, digit};
+    named!(pair<&str, (&str, &str)>, ws!(separated_pair!(alpha, tag!("->"), digit)));
+    let input = "a -> 1\nb -> 2\n\nc => 3\n";
+    let e = parse_lines(input, pair).unwrap_err();
+    assert_eq!((e.line, e.column, e.expected.as_str()), (4, 3, "token"));
+    let e = parse_lines("a -> 1 2", pair).unwrap_err();
+    assert_eq!((e.line, e.column, e.expected.as_str()), (1, 8, "end of line"));
+    assert_eq!(parse_lines("a -> 1\nb -> 2", pair), Ok(vec![("a", "1"), ("b", "2")]));
+}
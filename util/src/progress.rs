@@ -0,0 +1,194 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use libc;
+
+use args;
+
+/// Steps between checks of the clock, so `update` is cheap in tight loops.
+const CHECK_INTERVAL : u64 = 1 << 16;
+
+/// Time between redraws of the progress bar.
+const DRAW_INTERVAL : Duration = Duration::from_millis(100);
+
+const BAR_WIDTH : usize = 30;
+
+/// The time budget of each long-running loop in milliseconds, or 0 for none.
+static TIMEOUT_MS : AtomicU64 = AtomicU64::new(0);
+
+/// Sets the time budget of every `Progress` created afterwards.
+pub fn set_timeout(timeout : Option<Duration>) {
+    let ms = timeout.map_or(0, |timeout| u64::max(timeout.as_millis() as u64, 1));
+    TIMEOUT_MS.store(ms, Ordering::Relaxed);
+}
+
+fn timeout() -> Option<Duration> {
+    match TIMEOUT_MS.load(Ordering::Relaxed) {
+        0 => None,
+        ms => Some(Duration::from_millis(ms)),
+    }
+}
+
+/// Parses a timeout in (possibly fractional) seconds.
+pub fn parse_timeout(seconds : &str) -> Result<Duration, String> {
+    match seconds.parse::<f64>() {
+        Ok(seconds) if seconds > 0.0 && seconds.is_finite() => Ok(Duration::from_secs_f64(seconds)),
+        _ => Err(format!("invalid timeout: '{}'", seconds)),
+    }
+}
+
+/// The timeout given with `--timeout SECONDS`, if any.
+pub fn timeout_from_args() -> Result<Option<Duration>, String> {
+    args::parse_value_of("--timeout", parse_timeout)
+}
+
+/// A long-running loop that ran out of time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timeout {
+    pub label : String,
+    pub step : u64,
+    pub total : Option<u64>,
+    pub elapsed : Duration,
+}
+
+impl Timeout {
+    /// Describes the timeout along with the result computed so far.
+    pub fn with_partial<T : fmt::Display>(&self, partial : T) -> String {
+        format!("{}; partial result: {}", self, partial)
+    }
+}
+
+impl fmt::Display for Timeout {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: timed out after {:.1}s at step {}", self.label, self.elapsed.as_secs_f64(), self.step)?;
+        if let Some(total) = self.total {
+            write!(f, " of {} ({:.1}%)", total, 100.0 * self.step as f64 / total as f64)?;
+        }
+        Ok(())
+    }
+}
+
+/// Progress of a long-running loop, which calls `update` with the number of
+/// steps done so far. A progress bar with the rate and estimated time left is
+/// drawn on stderr if it is a tty, and the loop is stopped once the timeout set
+/// with `set_timeout` has passed.
+pub struct Progress {
+    label : String,
+    total : Option<u64>,
+    start : Instant,
+    deadline : Option<Instant>,
+    next_check : u64,
+    last_draw : Option<Instant>,
+    draw : bool,
+}
+
+impl Progress {
+    /// Starts tracking a loop of `total` steps (`None` if unknown).
+    pub fn new<S : Into<String>>(label : S, total : Option<u64>) -> Progress {
+        let start = Instant::now();
+        Progress {
+            label: label.into(),
+            total,
+            start,
+            deadline: timeout().map(|timeout| start + timeout),
+            next_check: CHECK_INTERVAL,
+            last_draw: None,
+            draw: unsafe { libc::isatty(libc::STDERR_FILENO) != 0 },
+        }
+    }
+
+    /// Never draws a progress bar, for loops that draw something else.
+    pub fn hidden(mut self) -> Progress {
+        self.draw = false;
+        self
+    }
+
+    /// Records that `step` steps are done, returning an error if the time budget
+    /// is used up.
+    #[inline]
+    pub fn update(&mut self, step : u64) -> Result<(), Timeout> {
+        if step < self.next_check {
+            return Ok(());
+        }
+        self.check(step)
+    }
+
+    fn check(&mut self, step : u64) -> Result<(), Timeout> {
+        self.next_check = step + CHECK_INTERVAL;
+        let now = Instant::now();
+        if self.deadline.is_some_and(|deadline| now >= deadline) {
+            return Err(Timeout { label: self.label.clone(), step, total: self.total, elapsed: now - self.start });
+        }
+        if self.draw && self.last_draw.is_none_or(|last| now - last >= DRAW_INTERVAL) {
+            self.last_draw = Some(now);
+            let line = self.render(step, now - self.start);
+            let _ = write!(io::stderr(), "\r{}\x1b[K", line);
+        }
+        Ok(())
+    }
+
+    fn render(&self, step : u64, elapsed : Duration) -> String {
+        let rate = step as f64 / elapsed.as_secs_f64().max(1e-9);
+        match self.total {
+            Some(total) if total > 0 => {
+                let fraction = f64::min(step as f64 / total as f64, 1.0);
+                let filled = (fraction * BAR_WIDTH as f64) as usize;
+                let eta = total.saturating_sub(step) as f64 / rate;
+                format!("{} [{}{}] {:5.1}% {} steps/s ETA {}", self.label, "#".repeat(filled), ".".repeat(BAR_WIDTH - filled),
+                        100.0 * fraction, human(rate), seconds(eta))
+            },
+            _ => format!("{} {} steps {} steps/s {} elapsed", self.label, human(step as f64), human(rate), seconds(elapsed.as_secs_f64())),
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        if self.last_draw.is_some() {
+            let _ = write!(io::stderr(), "\r\x1b[K");
+        }
+    }
+}
+
+/// Formats a count with a metric suffix, e.g. `1.2M`.
+fn human(n : f64) -> String {
+    match n {
+        n if n >= 1e9 => format!("{:.1}G", n / 1e9),
+        n if n >= 1e6 => format!("{:.1}M", n / 1e6),
+        n if n >= 1e3 => format!("{:.1}k", n / 1e3),
+        n => format!("{:.0}", n),
+    }
+}
+
+fn seconds(s : f64) -> String {
+    if s >= 3600.0 {
+        format!("{}h{:02}m", (s / 3600.0) as u64, ((s % 3600.0) / 60.0) as u64)
+    } else if s >= 60.0 {
+        format!("{}m{:02}s", (s / 60.0) as u64, (s % 60.0) as u64)
+    } else {
+        format!("{:.0}s", s)
+    }
+}
+
+#[test]
+fn test_render() {
+    let progress = Progress::new("spinning", Some(1000)).hidden();
+    assert_eq!(progress.render(250, Duration::from_secs(1)), "spinning [#######.......................]  25.0% 250 steps/s ETA 3s");
+    let progress = Progress::new("brute force", None).hidden();
+    assert_eq!(progress.render(3_000_000, Duration::from_secs(90)), "brute force 3.0M steps 33.3k steps/s 1m30s elapsed");
+    assert_eq!(parse_timeout("2.5"), Ok(Duration::from_millis(2500)));
+    assert_eq!(parse_timeout("-1"), Err(String::from("invalid timeout: '-1'")));
+    assert!(parse_timeout("abc").is_err());
+}
+
+#[test]
+fn test_timeout() {
+    let mut progress = Progress::new("looping", Some(1 << 18)).hidden();
+    progress.deadline = Some(progress.start);
+    assert!(progress.update(1).is_ok());
+    let timeout = progress.update(CHECK_INTERVAL).unwrap_err();
+    assert_eq!(timeout.step, CHECK_INTERVAL);
+    assert_eq!(timeout.with_partial(7), "looping: timed out after 0.0s at step 65536 of 262144 (25.0%); partial result: 7");
+}
@@ -0,0 +1,238 @@
+use std::env;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use rustyline::Editor;
+use rustyline::error::ReadlineError;
+
+use input::InputSource;
+use solution::Parts;
+
+/// How inputs are entered in an interactive session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Every line is a separate input.
+    Lines,
+    /// Inputs span several lines and are ended by a blank line.
+    Multiline,
+}
+
+const HELP : &str = "\
+commands:
+    :part 1|2|both  select the parts to solve
+    :load [FILE]    run the input in FILE (or reload the last file)
+    :run            run the last input again
+    :time           toggle printing how long each run takes
+    :show           print the session state and the last input
+    :reset          forget the last input and restore the starting settings
+    :help           print this message
+    :quit           end the session (or press Ctrl-D)";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Command {
+    Part(Parts),
+    Load(Option<String>),
+    Run,
+    Time,
+    Show,
+    Reset,
+    Help,
+    Quit,
+}
+
+fn parse_command(line : &str) -> Result<Command, String> {
+    let mut words = line.trim()[1..].split_whitespace();
+    let name = words.next().unwrap_or("");
+    let arg = words.next();
+    let command = match (name, arg) {
+        ("part", Some("1")) => Command::Part(Parts::One),
+        ("part", Some("2")) => Command::Part(Parts::Two),
+        ("part", Some("both")) => Command::Part(Parts::Both),
+        ("part", _) => return Err(String::from("usage: :part 1|2|both")),
+        ("load", file) => Command::Load(file.map(String::from)),
+        ("run", None) => Command::Run,
+        ("time", None) => Command::Time,
+        ("show", None) => Command::Show,
+        ("reset", None) => Command::Reset,
+        ("help", None) => Command::Help,
+        ("quit", None) | ("q", None) => Command::Quit,
+        _ => return Err(format!("unknown command '{}' (try :help)", line.trim())),
+    };
+    if words.next().is_some() {
+        return Err(format!("too many arguments to :{}", name));
+    }
+    Ok(command)
+}
+
+/// State kept between inputs in an interactive session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    /// The parts passed to each run.
+    pub parts : Parts,
+    /// Whether to print how long each run takes.
+    pub time : bool,
+    /// The last input run.
+    pub input : String,
+    /// The last file loaded with `:load`.
+    pub file : Option<String>,
+    initial : Parts,
+}
+
+impl Session {
+    pub fn new(parts : Parts) -> Session {
+        Session { parts, time: false, input: String::new(), file: None, initial: parts }
+    }
+
+    fn run<F>(&mut self, input : String, run : &mut F) where F : FnMut(&str, Parts) {
+        self.input = input;
+        self.rerun(run);
+    }
+
+    fn rerun<F>(&self, run : &mut F) where F : FnMut(&str, Parts) {
+        let start = Instant::now();
+        run(&self.input, self.parts);
+        if self.time {
+            println!("({:.3}ms)", start.elapsed().as_secs_f64() * 1000.0);
+        }
+    }
+
+    fn execute<F>(&mut self, command : Command, run : &mut F) where F : FnMut(&str, Parts) {
+        match command {
+            Command::Part(parts) => self.parts = parts,
+            Command::Load(file) => {
+                let file = match file.or_else(|| self.file.clone()) {
+                    Some(file) => file,
+                    None => {
+                        eprintln!("no file to reload");
+                        return;
+                    },
+                };
+                match InputSource::File(PathBuf::from(&file)).read() {
+                    Ok(input) => {
+                        self.file = Some(file);
+                        self.run(input, run);
+                    },
+                    Err(e) => eprintln!("error reading {}: {}", file, e),
+                }
+            },
+            Command::Run if self.input.is_empty() => eprintln!("nothing to run"),
+            Command::Run => self.rerun(run),
+            Command::Time => {
+                self.time = ! self.time;
+                println!("timing {}", if self.time { "on" } else { "off" });
+            },
+            Command::Show => {
+                println!("parts: {:?}", self.parts);
+                println!("timing: {}", if self.time { "on" } else { "off" });
+                println!("file: {}", self.file.as_deref().unwrap_or("none"));
+                println!("input: {} lines", self.input.lines().count());
+                for line in self.input.lines() {
+                    println!("    {}", line);
+                }
+            },
+            Command::Reset => {
+                *self = Session::new(self.initial);
+                println!("session reset");
+            },
+            Command::Help => println!("{}", HELP),
+            Command::Quit => { },
+        }
+    }
+}
+
+/// Where the history of every session is kept: `~/.aoc2017_history`.
+fn history_path() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".aoc2017_history"))
+}
+
+/// Runs an interactive session on stdin, with line editing and history. Each
+/// input entered is passed to `run` along with the parts selected; lines
+/// starting with `:` are commands (see `:help`). In multiline mode, `header` is
+/// printed before each input.
+pub fn run<F>(mode : Mode, header : &str, parts : Parts, mut run : F) where F : FnMut(&str, Parts) {
+    let mut editor = Editor::<()>::new();
+    let history = history_path();
+    if let Some(ref path) = history {
+        // there's no history yet on the first run
+        let _ = editor.load_history(path);
+    }
+
+    let mut session = Session::new(parts);
+    let mut buffer = String::new();
+    if mode == Mode::Multiline {
+        println!("{}", header);
+    }
+    loop {
+        let line = match editor.readline(if buffer.is_empty() { "> " } else { ". " }) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => {
+                buffer.clear();
+                continue;
+            },
+            Err(ReadlineError::Eof) => break,
+            Err(e) => {
+                eprintln!("error reading input: {}", e);
+                break;
+            },
+        };
+        if ! line.trim().is_empty() {
+            editor.add_history_entry(line.as_str());
+        }
+        if buffer.is_empty() && line.trim_start().starts_with(':') {
+            match parse_command(&line) {
+                Ok(Command::Quit) => break,
+                Ok(command) => session.execute(command, &mut run),
+                Err(e) => eprintln!("{}", e),
+            }
+            continue;
+        }
+        match mode {
+            Mode::Lines if line.trim().is_empty() => { },
+            Mode::Lines => session.run(String::from(line.trim_end()), &mut run),
+            Mode::Multiline if line.trim().is_empty() => {
+                if ! buffer.is_empty() {
+                    session.run(::std::mem::take(&mut buffer), &mut run);
+                    println!();
+                    println!("{}", header);
+                }
+            },
+            Mode::Multiline => {
+                buffer.push_str(&line);
+                buffer.push('\n');
+            },
+        }
+    }
+
+    if let Some(ref path) = history {
+        if let Err(e) = editor.save_history(path) {
+            eprintln!("error saving history to {}: {}", path.display(), e);
+        }
+    }
+}
+
+#[test]
+fn test_parse_command() {
+    assert_eq!(parse_command(":part 2"), Ok(Command::Part(Parts::Two)));
+    assert_eq!(parse_command(" :load in.txt"), Ok(Command::Load(Some(String::from("in.txt")))));
+    assert_eq!(parse_command(":load"), Ok(Command::Load(None)));
+    assert_eq!(parse_command(":q"), Ok(Command::Quit));
+    assert!(parse_command(":part 3").is_err());
+    assert!(parse_command(":time now").is_err());
+    assert!(parse_command(":jump").is_err());
+}
+
+#[test]
+fn test_session() {
+    let mut runs = Vec::new();
+    {
+        let mut record = |input : &str, parts : Parts| runs.push((String::from(input), parts));
+        let mut session = Session::new(Parts::Both);
+        session.execute(Command::Run, &mut record);
+        session.run(String::from("0 2 7 0"), &mut record);
+        session.execute(Command::Part(Parts::Two), &mut record);
+        session.execute(Command::Run, &mut record);
+        session.execute(Command::Reset, &mut record);
+        assert_eq!(session, Session::new(Parts::Both));
+    }
+    assert_eq!(runs, vec![(String::from("0 2 7 0"), Parts::Both), (String::from("0 2 7 0"), Parts::Two)]);
+}
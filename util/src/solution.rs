@@ -0,0 +1,121 @@
+use std::fmt::Display;
+use std::time::Instant;
+
+use args;
+use format::{Format, Report, collect_warnings};
+
+/// A puzzle solution split into a parsing stage and one stage for each part, so
+/// the parsed input can be reused (and each stage tested on its own).
+pub trait Solution {
+    /// Day of the puzzle (1-25).
+    const DAY : u32;
+
+    type Input;
+    type Answer1 : Display;
+    type Answer2 : Display;
+
+    /// Labels printed before the answer to each part.
+    const LABEL1 : &'static str = "part one";
+    const LABEL2 : &'static str = "part two";
+
+    fn parse(input : &str) -> Result<Self::Input, String>;
+    fn part1(input : &Self::Input) -> Result<Self::Answer1, String>;
+    fn part2(input : &Self::Input) -> Result<Self::Answer2, String>;
+}
+
+/// Which parts of a puzzle to solve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parts {
+    One,
+    Two,
+    Both,
+}
+
+impl Parts {
+    /// Both parts, unless only one was selected with `--part1` or `--part2`.
+    pub fn from_args() -> Parts {
+        match (args::is_present("--part1"), args::is_present("--part2")) {
+            (true, false) => Parts::One,
+            (false, true) => Parts::Two,
+            _ => Parts::Both,
+        }
+    }
+
+    pub fn part1(self) -> bool { self != Parts::Two }
+
+    pub fn part2(self) -> bool { self != Parts::One }
+}
+
+fn print_answer<A : Display>(label : &str, width : usize, answer : Result<A, String>) {
+    match answer {
+        Ok(answer) => println!("{:width$} {}", format!("{}:", label), answer, width = width),
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+fn solve_part<S, A, F>(input : &Result<S::Input, String>, part : u32, solve : F) -> Report
+    where S : Solution, A : Display, F : FnOnce(&S::Input) -> Result<A, String>
+{
+    let start = Instant::now();
+    let (answer, warnings) = collect_warnings(|| match *input {
+        Ok(ref input) => solve(input).map(|answer| answer.to_string()),
+        Err(ref e) => Err(e.clone()),
+    });
+    Report { day: S::DAY, part, answer, elapsed: start.elapsed(), warnings }
+}
+
+/// Parses `input` once and solves each of the selected parts, collecting any
+/// warnings. Warnings from parsing are included in the report for every part.
+pub fn solve<S : Solution>(input : &str, parts : Parts) -> Vec<Report> {
+    solve_with::<S, _>(input, parts, S::parse)
+}
+
+/// Like `solve`, but parses the input with `parse` instead of `S::parse`.
+pub fn solve_with<S, P>(input : &str, parts : Parts, parse : P) -> Vec<Report>
+    where S : Solution, P : FnOnce(&str) -> Result<S::Input, String>
+{
+    let (input, parse_warnings) = collect_warnings(|| parse(input));
+    let mut reports = Vec::new();
+    if parts.part1() {
+        reports.push(solve_part::<S, _, _>(&input, 1, S::part1));
+    }
+    if parts.part2() {
+        reports.push(solve_part::<S, _, _>(&input, 2, S::part2));
+    }
+    for report in &mut reports {
+        report.warnings.splice(0..0, parse_warnings.iter().cloned());
+    }
+    reports
+}
+
+/// Parses `input` once and prints the answer to each of the selected parts.
+pub fn print_answers<S : Solution>(input : &str, parts : Parts, format : Format) {
+    print_answers_with::<S, _>(input, parts, format, S::parse)
+}
+
+/// Like `print_answers`, but parses the input with `parse` instead of
+/// `S::parse`.
+pub fn print_answers_with<S, P>(input : &str, parts : Parts, format : Format, parse : P)
+    where S : Solution, P : FnOnce(&str) -> Result<S::Input, String>
+{
+    if format == Format::Json {
+        for report in solve_with::<S, _>(input, parts, parse) {
+            println!("{}", report.to_json());
+        }
+        return;
+    }
+    let input = match parse(input) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+    let width = usize::max(S::LABEL1.len(), S::LABEL2.len()) + 1;
+    if parts.part1() {
+        print_answer(S::LABEL1, width, S::part1(&input));
+    }
+    if parts.part2() {
+        print_answer(S::LABEL2, width, S::part2(&input));
+    }
+}
@@ -0,0 +1,96 @@
+//! Arithmetic on registers that never panics: what happens when a result
+//! doesn't fit in 64 bits depends on the `Arithmetic` mode, and `mod 0` is
+//! always a fault that stops the process.
+
+use std::fmt;
+
+use util::args;
+
+/// What to do with results that don't fit in 64 bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Arithmetic {
+    /// Stop the process with `Fault::Overflow`.
+    #[default]
+    Checked,
+    /// Wrap around, as two's complement.
+    Wrapping,
+    /// Use the closest value that fits.
+    Saturating,
+}
+
+impl Arithmetic {
+    pub fn parse(mode : &str) -> Result<Arithmetic, String> {
+        match mode {
+            "checked" => Ok(Arithmetic::Checked),
+            "wrapping" => Ok(Arithmetic::Wrapping),
+            "saturating" => Ok(Arithmetic::Saturating),
+            _ => Err(format!("invalid arithmetic mode: '{}' (expected checked, wrapping or saturating)", mode)),
+        }
+    }
+
+    /// The mode given with `--arithmetic MODE` (or `--arithmetic=MODE`) in the
+    /// program arguments, `Checked` by default.
+    pub fn from_args() -> Result<Arithmetic, String> {
+        Ok(args::parse_value_of("--arithmetic", Arithmetic::parse)?.unwrap_or_default())
+    }
+
+    /// Applies `op` to `a` and `b`.
+    #[inline]
+    pub fn apply(self, op : BinOp, a : i64, b : i64) -> Result<i64, Fault> {
+        if op == BinOp::Mod && b == 0 {
+            return Err(Fault::ModByZero);
+        }
+        match self {
+            Arithmetic::Checked => match op {
+                BinOp::Add => a.checked_add(b),
+                BinOp::Sub => a.checked_sub(b),
+                BinOp::Mul => a.checked_mul(b),
+                BinOp::Mod => a.checked_rem(b),
+            }.ok_or(Fault::Overflow),
+            Arithmetic::Wrapping => Ok(match op {
+                BinOp::Add => a.wrapping_add(b),
+                BinOp::Sub => a.wrapping_sub(b),
+                BinOp::Mul => a.wrapping_mul(b),
+                BinOp::Mod => a.wrapping_rem(b),
+            }),
+            Arithmetic::Saturating => Ok(match op {
+                BinOp::Add => a.saturating_add(b),
+                BinOp::Sub => a.saturating_sub(b),
+                BinOp::Mul => a.saturating_mul(b),
+                // the remainder is 0 even when the quotient doesn't fit
+                BinOp::Mod => a.wrapping_rem(b),
+            }),
+        }
+    }
+}
+
+/// An arithmetic operation of an instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    /// The remainder, with the sign of the dividend.
+    Mod,
+}
+
+/// Why an instruction couldn't be executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// The result didn't fit in 64 bits, with `Arithmetic::Checked`.
+    Overflow,
+    ModByZero,
+    /// The target of a jump doesn't fit in 64 bits, whatever the arithmetic
+    /// mode.
+    JumpOverflow,
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Fault::Overflow => write!(f, "arithmetic overflow"),
+            Fault::ModByZero => write!(f, "mod by zero"),
+            Fault::JumpOverflow => write!(f, "jump target overflow"),
+        }
+    }
+}
@@ -0,0 +1,170 @@
+//! A compact binary encoding of programs, for saving them once they're parsed
+//! (and possibly optimized) and loading them back exactly.
+//!
+//! A bytecode file starts with a header:
+//!
+//! - the magic bytes `aocvm`,
+//! - the format version, one byte (currently 1),
+//! - the name of the instruction set (`Encode::NAME`), one byte for its
+//!   length followed by the name,
+//! - the number of instructions, 4 bytes little endian,
+//!
+//! followed by each instruction: its opcode, one byte, and then its operands.
+//! An operand is a single byte for a register (0 for `a`, 1 for `b`, ...),
+//! `SMALL` followed by a signed byte for a number from -128 to 127, or `NUM`
+//! followed by 8 bytes little endian for any other number.
+
+use std::fs::File;
+use std::io::{Read, Write};
+
+use {InstructionSet, Register, Value};
+
+const MAGIC : &[u8] = b"aocvm";
+pub const VERSION : u8 = 1;
+/// Tag of a number that fits in one byte.
+const SMALL : u8 = 0xfe;
+/// Tag of a number that needs 8 bytes.
+const NUM : u8 = 0xff;
+
+/// Instructions that can be saved as bytecode.
+pub trait Encode : InstructionSet {
+    /// Name of the instruction set, so that programs aren't loaded into the
+    /// wrong one.
+    const NAME : &'static str;
+
+    /// The opcode of the instruction and its operands, with registers as
+    /// `Value::Reg`.
+    fn encode(&self) -> (u8, Vec<Value>);
+
+    /// Builds an instruction from its opcode, reading its operands with
+    /// `operands`.
+    fn decode_bytes(opcode : u8, operands : &mut Reader) -> Result<Self, String>;
+}
+
+/// Reads the operands of an instruction, for `Encode::decode_bytes`. Errors
+/// give the offset of the offending byte.
+pub struct Reader<'a> {
+    bytes : &'a [u8],
+    pos : usize,
+    registers : usize,
+}
+
+impl<'a> Reader<'a> {
+    fn error(&self, expected : &str) -> String {
+        format!("invalid bytecode at byte {}: expected {}", self.pos, expected)
+    }
+
+    fn take(&mut self, n : usize, expected : &str) -> Result<&'a [u8], String> {
+        if self.bytes.len() - self.pos < n {
+            return Err(self.error(expected));
+        }
+        self.pos += n;
+        Ok(&self.bytes[self.pos - n..self.pos])
+    }
+
+    fn byte(&mut self, expected : &str) -> Result<u8, String> {
+        self.take(1, expected).map(|bytes| bytes[0])
+    }
+
+    /// The error for an unknown opcode, which was the last byte read.
+    pub fn unknown(&mut self) -> String {
+        self.pos -= 1;
+        self.error("known opcode")
+    }
+
+    /// The next operand, which must be a register.
+    pub fn register(&mut self) -> Result<Register, String> {
+        match self.byte("register")? {
+            r if (r as usize) < self.registers => Ok((b'a' + r) as char),
+            _ => {
+                self.pos -= 1;
+                Err(self.error("register"))
+            },
+        }
+    }
+
+    /// The next operand, which may be a register or a number.
+    pub fn value(&mut self) -> Result<Value, String> {
+        let expected = "register or number";
+        match self.byte(expected)? {
+            r if (r as usize) < self.registers => Ok(Value::Reg((b'a' + r) as char)),
+            SMALL => self.byte("number").map(|n| Value::Num(i64::from(n as i8))),
+            NUM => {
+                let mut n = [0; 8];
+                n.copy_from_slice(self.take(8, "number")?);
+                Ok(Value::Num(i64::from_le_bytes(n)))
+            },
+            _ => {
+                self.pos -= 1;
+                Err(self.error(expected))
+            },
+        }
+    }
+}
+
+/// Encodes a program as bytecode.
+pub fn to_bytes<I : Encode>(program : &[I]) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.push(VERSION);
+    bytes.push(I::NAME.len() as u8);
+    bytes.extend(I::NAME.as_bytes());
+    bytes.extend(&(program.len() as u32).to_le_bytes());
+    for instruction in program {
+        let (opcode, operands) = instruction.encode();
+        bytes.push(opcode);
+        for operand in operands {
+            match operand {
+                Value::Reg(r) => bytes.push(r as u8 - b'a'),
+                Value::Num(n) if n as i8 as i64 == n => bytes.extend(&[SMALL, n as u8]),
+                Value::Num(n) => {
+                    bytes.push(NUM);
+                    bytes.extend(&n.to_le_bytes());
+                },
+            }
+        }
+    }
+    bytes
+}
+
+/// Decodes a program encoded by `to_bytes`.
+pub fn from_bytes<I : Encode>(bytes : &[u8]) -> Result<Vec<I>, String> {
+    let mut reader = Reader { bytes, pos: 0, registers: I::REGISTERS };
+    if reader.take(MAGIC.len(), "bytecode header").ok() != Some(MAGIC) {
+        return Err(String::from("not a bytecode file"));
+    }
+    match reader.byte("version")? {
+        VERSION => {},
+        version => return Err(format!("unsupported bytecode version {} (expected {})", version, VERSION)),
+    }
+    let len = reader.byte("instruction set")? as usize;
+    let name = reader.take(len, "instruction set")?;
+    if name != I::NAME.as_bytes() {
+        return Err(format!("bytecode is for {} programs, not {}", String::from_utf8_lossy(name), I::NAME));
+    }
+    let mut count = [0; 4];
+    count.copy_from_slice(reader.take(4, "number of instructions")?);
+    let count = u32::from_le_bytes(count);
+    let mut program = Vec::new();
+    for _ in 0..count {
+        let opcode = reader.byte("opcode")?;
+        program.push(I::decode_bytes(opcode, &mut reader)?);
+    }
+    if reader.pos < bytes.len() {
+        return Err(reader.error("end of file"));
+    }
+    Ok(program)
+}
+
+/// Saves a program as bytecode in a file.
+pub fn save<I : Encode>(program : &[I], path : &str) -> Result<(), String> {
+    File::create(path).and_then(|mut file| file.write_all(&to_bytes(program)))
+        .map_err(|e| format!("can't write {}: {}", path, e))
+}
+
+/// Loads a program saved with `save`.
+pub fn load<I : Encode>(path : &str) -> Result<Vec<I>, String> {
+    let mut bytes = Vec::new();
+    File::open(path).and_then(|mut file| file.read_to_end(&mut bytes))
+        .map_err(|e| format!("can't read {}: {}", path, e))?;
+    from_bytes(&bytes).map_err(|e| format!("{}: {}", path, e))
+}
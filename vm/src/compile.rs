@@ -0,0 +1,262 @@
+//! Ahead-of-time compilation of programs to closures, for running them faster
+//! than `Process` can.
+//!
+//! Each instruction is lowered once to a closure specialized for its operands:
+//! registers are resolved to indices, numbers are captured as they are, and
+//! jumps by a number are resolved to the line they go to. Running the program
+//! then only calls the closure for each line in turn, without matching on the
+//! instruction or its operands again.
+//!
+//! Compiled programs can't be profiled, traced or checked for loops; use
+//! `Process` for that. Their arithmetic mode (see `arith`) is chosen when they
+//! are compiled.
+
+use {Effect, Fault, InstructionSet, Register, Registers, Value};
+use arith::{Arithmetic, BinOp};
+
+/// What a compiled instruction does to the flow of the process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    /// Go on to the next instruction.
+    Next,
+    /// Go to the instruction at the given line (counted from 0), which may be
+    /// outside the program.
+    Goto(i64),
+    /// Send a value and go on to the next instruction.
+    Send(i64),
+    /// Wait for a value to store in the register (see `Runner::receive`).
+    Receive(Register),
+    /// Stop, since the instruction can't be executed.
+    Fault(Fault),
+}
+
+/// A compiled instruction.
+pub type Op = Box<dyn Fn(&mut Registers) -> Flow>;
+
+/// Instructions that can be compiled to closures.
+pub trait Compile : InstructionSet + 'static {
+    /// Lowers the instruction at line `ip` (counted from 0), for registers
+    /// using `mode`. By default, the closure just executes the instruction.
+    fn compile(&self, ip : i64, _mode : Arithmetic) -> Op { interpret(*self, ip) }
+}
+
+/// A closure executing `instruction`, for instructions with no faster
+/// lowering.
+pub fn interpret<I : InstructionSet + 'static>(instruction : I, ip : i64) -> Op {
+    Box::new(move |registers| match instruction.execute(registers) {
+        Effect::Next => Flow::Next,
+        Effect::Jump(offset) => goto(ip, offset),
+        Effect::Send(x) => Flow::Send(x),
+        Effect::Receive(r) => Flow::Receive(r),
+        Effect::Fault(fault) => Flow::Fault(fault),
+    })
+}
+
+/// The flow of a jump by `offset` from line `ip`, which faults if the target
+/// doesn't fit in 64 bits.
+#[inline]
+fn goto(ip : i64, offset : i64) -> Flow {
+    ip.checked_add(offset).map_or(Flow::Fault(Fault::JumpOverflow), Flow::Goto)
+}
+
+/// Compiles `set r v`.
+pub fn set(r : Register, v : Value) -> Op {
+    let r = Registers::index(r);
+    match v {
+        Value::Num(n) => Box::new(move |registers| {
+            registers.store(r, n);
+            Flow::Next
+        }),
+        Value::Reg(s) => {
+            let s = Registers::index(s);
+            Box::new(move |registers| {
+                let x = registers.load(s);
+                registers.store(r, x);
+                Flow::Next
+            })
+        },
+    }
+}
+
+/// Compiles an instruction storing `r op v` in `r`, in the arithmetic `mode`.
+pub fn binary(r : Register, v : Value, op : BinOp, mode : Arithmetic) -> Op {
+    use arith::Arithmetic::*;
+    let overflow = |x : Option<i64>| x.ok_or(Fault::Overflow);
+    match (mode, op) {
+        (_, BinOp::Mod) if v == Value::Num(0) => Box::new(|_| Flow::Fault(Fault::ModByZero)),
+        (Checked, BinOp::Add) => binary_with(r, v, move |a, b| overflow(a.checked_add(b))),
+        (Checked, BinOp::Sub) => binary_with(r, v, move |a, b| overflow(a.checked_sub(b))),
+        (Checked, BinOp::Mul) => binary_with(r, v, move |a, b| overflow(a.checked_mul(b))),
+        (Wrapping, BinOp::Add) => binary_with(r, v, |a, b| Ok(a.wrapping_add(b))),
+        (Wrapping, BinOp::Sub) => binary_with(r, v, |a, b| Ok(a.wrapping_sub(b))),
+        (Wrapping, BinOp::Mul) => binary_with(r, v, |a, b| Ok(a.wrapping_mul(b))),
+        (Saturating, BinOp::Add) => binary_with(r, v, |a, b| Ok(a.saturating_add(b))),
+        (Saturating, BinOp::Sub) => binary_with(r, v, |a, b| Ok(a.saturating_sub(b))),
+        (Saturating, BinOp::Mul) => binary_with(r, v, |a, b| Ok(a.saturating_mul(b))),
+        (mode, BinOp::Mod) => binary_with(r, v, move |a, b| mode.apply(BinOp::Mod, a, b)),
+    }
+}
+
+fn binary_with<F>(r : Register, v : Value, op : F) -> Op where F : Fn(i64, i64) -> Result<i64, Fault> + 'static {
+    let r = Registers::index(r);
+    let store = move |registers : &mut Registers, x, y| match op(x, y) {
+        Ok(z) => {
+            registers.store(r, z);
+            Flow::Next
+        },
+        Err(fault) => Flow::Fault(fault),
+    };
+    match v {
+        Value::Num(n) => Box::new(move |registers| {
+            let x = registers.load(r);
+            store(registers, x, n)
+        }),
+        Value::Reg(s) => {
+            let s = Registers::index(s);
+            Box::new(move |registers| {
+                let (x, y) = (registers.load(r), registers.load(s));
+                store(registers, x, y)
+            })
+        },
+    }
+}
+
+/// Compiles the instruction at line `ip` jumping by `offset` if `test(x)`.
+/// Jumps whose test is always true or always false don't check it at all.
+pub fn jump_if<F>(x : Value, test : F, offset : Value, ip : i64) -> Op where F : Fn(i64) -> bool + 'static {
+    match (x, offset) {
+        (Value::Num(x), _) if ! test(x) => Box::new(|_| Flow::Next),
+        (Value::Num(_), Value::Num(offset)) => {
+            let flow = goto(ip, offset);
+            Box::new(move |_| flow)
+        },
+        (Value::Num(_), Value::Reg(o)) => {
+            let o = Registers::index(o);
+            Box::new(move |registers| goto(ip, registers.load(o)))
+        },
+        (Value::Reg(x), Value::Num(offset)) => {
+            let (x, flow) = (Registers::index(x), goto(ip, offset));
+            Box::new(move |registers| if test(registers.load(x)) { flow } else { Flow::Next })
+        },
+        (Value::Reg(x), Value::Reg(o)) => {
+            let (x, o) = (Registers::index(x), Registers::index(o));
+            Box::new(move |registers| if test(registers.load(x)) { goto(ip, registers.load(o)) } else { Flow::Next })
+        },
+    }
+}
+
+/// Compiles an instruction sending `v`.
+pub fn send(v : Value) -> Op {
+    match v {
+        Value::Num(n) => Box::new(move |_| Flow::Send(n)),
+        Value::Reg(r) => {
+            let r = Registers::index(r);
+            Box::new(move |registers| Flow::Send(registers.load(r)))
+        },
+    }
+}
+
+/// A compiled program.
+pub struct Compiled {
+    ops : Vec<Op>,
+    mode : Arithmetic,
+}
+
+impl Compiled {
+    /// The program compiled with checked arithmetic.
+    pub fn new<I : Compile>(program : &[I]) -> Compiled {
+        Compiled::with_mode(program, Arithmetic::default())
+    }
+
+    pub fn with_mode<I : Compile>(program : &[I], mode : Arithmetic) -> Compiled {
+        Compiled {
+            ops: program.iter().enumerate().map(|(ip, i)| i.compile(ip as i64, mode)).collect(),
+            mode,
+        }
+    }
+
+    pub fn len(&self) -> usize { self.ops.len() }
+
+    pub fn is_empty(&self) -> bool { self.ops.is_empty() }
+}
+
+/// Why `Runner::run` returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    /// A value was sent.
+    Sending(i64),
+    /// The process is waiting for a value to store in the register, and won't
+    /// go on until it is given one with `Runner::receive`.
+    Waiting(Register),
+    /// The instruction pointer is outside the program.
+    Halted(i64),
+    /// The instruction at the instruction pointer couldn't be executed.
+    Faulted(i64, Fault),
+    /// The process ran as many steps as it was allowed to.
+    OutOfSteps,
+}
+
+/// A compiled program running on its own registers, like `Process`.
+pub struct Runner<'a> {
+    code : &'a Compiled,
+    registers : Registers,
+    ip : i64,
+    steps : u64,
+}
+
+impl<'a> Runner<'a> {
+    pub fn new(code : &'a Compiled) -> Runner<'a> {
+        Runner { code, registers: Registers::with_mode(code.mode), ip: 0, steps: 0 }
+    }
+
+    /// Runs at most `max_steps` instructions, stopping early to send or
+    /// receive a value or when the program halts.
+    pub fn run(&mut self, max_steps : u64) -> Stop {
+        let ops = &self.code.ops;
+        let end = self.steps.saturating_add(max_steps);
+        while self.steps < end {
+            // a negative ip wraps around to an index past the end
+            let op = match ops.get(self.ip as usize) {
+                Some(op) => op,
+                None => return Stop::Halted(self.ip),
+            };
+            match op(&mut self.registers) {
+                Flow::Next => self.ip += 1,
+                Flow::Goto(ip) => self.ip = ip,
+                Flow::Send(x) => {
+                    self.ip += 1;
+                    self.steps += 1;
+                    return Stop::Sending(x);
+                },
+                Flow::Receive(r) => return Stop::Waiting(r),
+                Flow::Fault(fault) => return Stop::Faulted(self.ip, fault),
+            }
+            self.steps += 1;
+        }
+        Stop::OutOfSteps
+    }
+
+    /// Stores a value the process is waiting for, and moves past the instruction
+    /// that received it.
+    pub fn receive(&mut self, reg : Register, val : i64) {
+        self.registers.set(reg, val);
+        self.steps += 1;
+        self.ip += 1;
+    }
+
+    pub fn get_register(&self, reg : Register) -> i64 {
+        self.registers.get(Value::Reg(reg))
+    }
+
+    pub fn set_register(&mut self, reg : Register, val : i64) {
+        self.registers.set(reg, val);
+    }
+
+    pub fn registers(&self) -> &Registers { &self.registers }
+
+    /// Index of the next instruction.
+    pub fn ip(&self) -> i64 { self.ip }
+
+    /// Number of instructions executed so far.
+    pub fn steps(&self) -> u64 { self.steps }
+}
@@ -0,0 +1,260 @@
+use std::collections::BTreeSet;
+use std::fmt::Write as FmtWrite;
+use std::io::{self, BufRead, Write};
+
+use {InstructionSet, Process, Register, State};
+
+/// One or more processes and whatever connects them, run one step at a time
+/// under the debugger.
+pub trait Machine<'a> {
+    type Instruction : InstructionSet + 'a;
+
+    /// Runs every process for one step, returning why the machine stopped if it
+    /// can't go on.
+    fn tick(&mut self) -> Option<String>;
+
+    fn processes(&self) -> Vec<&Process<'a, Self::Instruction>>;
+
+    fn processes_mut(&mut self) -> Vec<&mut Process<'a, Self::Instruction>>;
+
+    /// Other state worth showing, like message queues.
+    fn describe(&self) -> Vec<String> { Vec::new() }
+}
+
+/// A single process that runs until it halts. Sent values are ignored, and
+/// the machine stops if the process waits to receive one.
+impl<'a, I : InstructionSet + 'a> Machine<'a> for Process<'a, I> {
+    type Instruction = I;
+
+    fn tick(&mut self) -> Option<String> {
+        match self.step() {
+            State::Ran(_) | State::Sending(_) => None,
+            State::Waiting(r) => Some(format!("waiting to receive into {} with nothing to receive", r)),
+            State::Halted(ip) => Some(format!("halted at line {}", ip.saturating_add(1))),
+            State::Faulted(ip, fault) => Some(format!("{} at line {}", fault, ip + 1)),
+        }
+    }
+
+    fn processes(&self) -> Vec<&Process<'a, I>> { vec![self] }
+
+    fn processes_mut(&mut self) -> Vec<&mut Process<'a, I>> { vec![self] }
+}
+
+const HELP : &str = "\
+commands:
+    s, step [N]        run N steps (default 1)
+    c, continue        run until a breakpoint, watchpoint or the end
+    b, break LINE      stop before running LINE
+    w, watch REG       stop when REG changes in any process
+    d, delete LINE|REG remove a breakpoint or watchpoint
+    r, regs            print the registers
+    l, list            print the program with the current positions
+    i, info            print breakpoints, watchpoints and the machine state
+    h, help            print this message
+    q, quit            end the session";
+
+/// Runs a machine under user control. Lines are numbered from 1, like the
+/// lines of the program.
+pub struct Debugger<'a, M : Machine<'a>> {
+    machine : M,
+    breakpoints : BTreeSet<usize>,
+    watchpoints : BTreeSet<Register>,
+    finished : Option<String>,
+    steps : u64,
+    _program : ::std::marker::PhantomData<&'a ()>,
+}
+
+impl<'a, M : Machine<'a>> Debugger<'a, M> {
+    pub fn new(machine : M) -> Debugger<'a, M> {
+        Debugger {
+            machine,
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeSet::new(),
+            finished: None,
+            steps: 0,
+            _program: ::std::marker::PhantomData,
+        }
+    }
+
+    fn label(&self, pid : usize) -> String {
+        if self.machine.processes().len() == 1 { String::new() } else { format!("p{}: ", pid) }
+    }
+
+    fn register_names() -> impl Iterator<Item = Register> {
+        (b'a'..).take(M::Instruction::REGISTERS).map(char::from)
+    }
+
+    /// Runs one step, returning why execution stopped, if it did.
+    fn tick(&mut self) -> Option<String> {
+        if let Some(ref reason) = self.finished {
+            return Some(format!("finished: {}", reason));
+        }
+        let before : Vec<Vec<i64>> = self.machine.processes().iter()
+            .map(|p| self.watchpoints.iter().map(|&r| p.get_register(r)).collect())
+            .collect();
+        self.steps += 1;
+        if let Some(reason) = self.machine.tick() {
+            self.finished = Some(reason.clone());
+            return Some(format!("finished: {}", reason));
+        }
+        let mut stops = Vec::new();
+        for (pid, (process, before)) in self.machine.processes().iter().zip(before).enumerate() {
+            for (&r, old) in self.watchpoints.iter().zip(before) {
+                let new = process.get_register(r);
+                if new != old {
+                    stops.push(format!("{}watch {}: {} -> {}", self.label(pid), r, old, new));
+                }
+            }
+            let line = process.ip() + 1;
+            if line > 0 && self.breakpoints.contains(&(line as usize)) {
+                stops.push(format!("{}breakpoint at line {}", self.label(pid), line));
+            }
+        }
+        if stops.is_empty() { None } else { Some(stops.join("\n")) }
+    }
+
+    fn location(&self) -> String {
+        let mut text = String::new();
+        for (pid, process) in self.machine.processes().iter().enumerate() {
+            let ip = process.ip();
+            match process.program().get(ip as usize) {
+                Some(instruction) if ip >= 0 => writeln!(text, "{}line {}: {}", self.label(pid), ip + 1, instruction),
+                _ => writeln!(text, "{}outside the program (line {})", self.label(pid), ip.saturating_add(1)),
+            }.unwrap();
+        }
+        text.pop();
+        text
+    }
+
+    fn registers(&self) -> String {
+        let lines : Vec<String> = self.machine.processes().iter().enumerate().map(|(pid, process)| {
+            let values : Vec<String> = Self::register_names()
+                .map(|r| format!("{}={}", r, process.get_register(r)))
+                .collect();
+            format!("{}{}", self.label(pid), values.join(" "))
+        }).collect();
+        lines.join("\n")
+    }
+
+    fn listing(&self) -> String {
+        let processes = self.machine.processes();
+        let program = processes[0].program();
+        let lines : Vec<String> = program.iter().enumerate().map(|(i, instruction)| {
+            let here : Vec<String> = processes.iter().enumerate()
+                .filter(|&(_, p)| p.ip() == i as i64)
+                .map(|(pid, _)| if processes.len() == 1 { String::from("=>") } else { format!("p{}", pid) })
+                .collect();
+            let mark = if self.breakpoints.contains(&(i + 1)) { '*' } else { ' ' };
+            format!("{:>5} {}{:3}: {}", here.join(","), mark, i + 1, instruction)
+        }).collect();
+        lines.join("\n")
+    }
+
+    fn info(&self) -> String {
+        let breakpoints : Vec<String> = self.breakpoints.iter().map(usize::to_string).collect();
+        let watchpoints : Vec<String> = self.watchpoints.iter().map(char::to_string).collect();
+        let mut lines = vec![
+            format!("steps: {}", self.steps),
+            format!("breakpoints: {}", if breakpoints.is_empty() { String::from("none") } else { breakpoints.join(", ") }),
+            format!("watchpoints: {}", if watchpoints.is_empty() { String::from("none") } else { watchpoints.join(", ") }),
+        ];
+        if let Some(ref reason) = self.finished {
+            lines.push(format!("finished: {}", reason));
+        }
+        lines.extend(self.machine.describe());
+        lines.join("\n")
+    }
+
+    fn parse_register(word : Option<&str>) -> Result<Register, String> {
+        match word.map(|w| (w.len(), w.chars().next().unwrap())) {
+            Some((1, r)) if Self::register_names().any(|name| name == r) => Ok(r),
+            _ => Err(format!("expected a register from a to {}", Self::register_names().last().unwrap())),
+        }
+    }
+
+    fn parse_line(&self, word : Option<&str>) -> Result<usize, String> {
+        let len = self.machine.processes()[0].program().len();
+        match word.map(str::parse) {
+            Some(Ok(line)) if line >= 1 && line <= len => Ok(line),
+            _ => Err(format!("expected a line from 1 to {}", len)),
+        }
+    }
+
+    /// Runs a debugger command, returning its output, or `None` to quit.
+    pub fn command(&mut self, line : &str) -> Option<String> {
+        let mut words = line.split_whitespace();
+        let output = match words.next() {
+            None => Ok(String::new()),
+            Some("s") | Some("step") => match words.next().map(str::parse::<u64>).unwrap_or(Ok(1)) {
+                Ok(n) => {
+                    let stop = (0..n).filter_map(|_| self.tick()).next();
+                    Ok(stop.map_or_else(|| self.location(), |stop| format!("{}\n{}", stop, self.location())))
+                },
+                Err(_) => Err(String::from("expected a number of steps")),
+            },
+            Some("c") | Some("continue") => {
+                let stop = loop {
+                    if let Some(stop) = self.tick() {
+                        break stop;
+                    }
+                };
+                Ok(format!("{}\n{}", stop, self.location()))
+            },
+            Some("b") | Some("break") => self.parse_line(words.next()).map(|line| {
+                self.breakpoints.insert(line);
+                format!("breakpoint at line {}", line)
+            }),
+            Some("w") | Some("watch") => Self::parse_register(words.next()).map(|r| {
+                self.watchpoints.insert(r);
+                format!("watching {}", r)
+            }),
+            Some("d") | Some("delete") => match words.next() {
+                Some(word) if word.parse::<usize>().is_ok() => self.parse_line(Some(word)).map(|line| {
+                    self.breakpoints.remove(&line);
+                    format!("deleted breakpoint at line {}", line)
+                }),
+                word => Self::parse_register(word).map(|r| {
+                    self.watchpoints.remove(&r);
+                    format!("stopped watching {}", r)
+                }),
+            },
+            Some("r") | Some("regs") => Ok(self.registers()),
+            Some("l") | Some("list") => Ok(self.listing()),
+            Some("i") | Some("info") => Ok(self.info()),
+            Some("h") | Some("help") => Ok(String::from(HELP)),
+            Some("q") | Some("quit") => return None,
+            Some(command) => Err(format!("unknown command '{}' (try help)", command)),
+        };
+        Some(output.unwrap_or_else(|e| e))
+    }
+
+    pub fn machine(&self) -> &M { &self.machine }
+}
+
+/// Runs an interactive debugging session, reading commands from stdin.
+pub fn debug<'a, M : Machine<'a>>(machine : M) {
+    let mut debugger = Debugger::new(machine);
+    println!("{}", debugger.location());
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("(debug) ");
+        io::stdout().flush().unwrap();
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            _ => break,
+        };
+        match debugger.command(&line) {
+            Some(output) => if ! output.is_empty() {
+                println!("{}", output);
+            },
+            None => break,
+        }
+    }
+}
+
+/// Formats a list of values for `Machine::describe`.
+pub fn format_values<I : IntoIterator<Item = i64>>(values : I) -> String {
+    let values : Vec<String> = values.into_iter().map(|v| v.to_string()).collect();
+    format!("[{}]", values.join(", "))
+}
@@ -0,0 +1,424 @@
+//! A small virtual machine for the register-based assembly languages of day 18
+//! (Duet) and day 23 (Coprocessor Conflation). Each day defines its own
+//! instruction type and implements `InstructionSet` for it; parsing, registers
+//! and the fetch-execute loop are shared.
+
+extern crate util;
+
+pub mod arith;
+pub mod bytecode;
+pub mod compile;
+pub mod debug;
+mod parse;
+mod process;
+pub mod trace;
+pub mod watchdog;
+
+pub use arith::{Arithmetic, Fault};
+pub use bytecode::Encode;
+pub use compile::{Compile, Compiled, Runner};
+pub use debug::{Debugger, Machine};
+pub use parse::{Operands, disassemble, parse_program};
+pub use process::{Process, Registers, State};
+pub use trace::{Profile, Trace};
+pub use watchdog::{Limits, Termination, Watchdog};
+
+use std::fmt;
+
+use util::{ParseError, Solution};
+
+/// A register name, a lowercase letter starting from `a`.
+pub type Register = char;
+
+/// An instruction operand: the contents of a register or a number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value {
+    Reg(Register),
+    Num(i64)
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Reg(r) => write!(f, "{}", r),
+            Value::Num(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+/// How a program runs: the arithmetic of its registers, and the limits checked
+/// by its `Watchdog`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Config {
+    pub arithmetic : Arithmetic,
+    pub limits : Limits,
+}
+
+impl Config {
+    /// The config given with `--arithmetic`, `--max-steps` and `--detect-loops`
+    /// in the program arguments.
+    pub fn from_args() -> Result<Config, String> {
+        Ok(Config { arithmetic: Arithmetic::from_args()?, limits: Limits::from_args()? })
+    }
+}
+
+/// A parsed program and the `Config` to run it with, as the input of a day's
+/// `Solution`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program<I> {
+    pub instructions : Vec<I>,
+    pub config : Config,
+}
+
+impl<I> Program<I> {
+    /// The program with the default config.
+    pub fn new(instructions : Vec<I>) -> Program<I> {
+        Program { instructions, config: Config::default() }
+    }
+}
+
+/// A `Solution` whose input is a `Program`, which can run with any `Config`.
+pub trait Configurable : Solution {
+    /// Parses the input, to run with `config`.
+    fn parse_with(input : &str, config : Config) -> Result<Self::Input, String>;
+}
+
+impl<S, I> Configurable for S where S : Solution<Input = Program<I>> {
+    fn parse_with(input : &str, config : Config) -> Result<Program<I>, String> {
+        let mut program = S::parse(input)?;
+        program.config = config;
+        Ok(program)
+    }
+}
+
+/// What executing an instruction does to the flow of the process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    /// Go on to the next instruction.
+    Next,
+    /// Jump by the given offset from the current instruction.
+    Jump(i64),
+    /// Send a value and go on to the next instruction.
+    Send(i64),
+    /// Wait for a value to store in the register (see `Process::receive`).
+    Receive(Register),
+    /// Stop, since the instruction can't be executed.
+    Fault(Fault),
+}
+
+/// The instructions of one assembly language, displayed as they are written.
+pub trait InstructionSet : Copy + fmt::Debug + fmt::Display {
+    /// Number of registers, named from `a`.
+    const REGISTERS : usize;
+
+    /// Builds an instruction from its mnemonic and operands.
+    fn decode(operands : &Operands) -> Result<Self, ParseError>;
+
+    /// Executes the instruction on the registers.
+    fn execute(&self, registers : &mut Registers) -> Effect;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Counts down from a number, sending each value.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Countdown {
+        Set(Register, Value),
+        Dec(Register),
+        Out(Value),
+        Jnz(Value, Value),
+    }
+    use self::Countdown::*;
+
+    impl InstructionSet for Countdown {
+        const REGISTERS : usize = 2;
+
+        fn decode(args : &Operands) -> Result<Countdown, ParseError> {
+            Ok(match args.mnemonic() {
+                "set" => Set(args.register(0)?, args.value(1)?),
+                "dec" => Dec(args.register(0)?),
+                "out" => Out(args.value(0)?),
+                "jnz" => Jnz(args.value(0)?, args.offset(1)?),
+                _ => return Err(args.unknown()),
+            })
+        }
+
+        fn execute(&self, registers : &mut Registers) -> Effect {
+            match *self {
+                Set(r, v) => {
+                    let v = registers.get(v);
+                    registers.set(r, v);
+                },
+                Dec(r) => return registers.sub(r, Value::Num(1)),
+                Out(v) => return Effect::Send(registers.get(v)),
+                Jnz(x, y) => if registers.get(x) != 0 {
+                    return Effect::Jump(registers.get(y));
+                },
+            }
+            Effect::Next
+        }
+    }
+
+    impl fmt::Display for Countdown {
+        fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+            match *self {
+                Set(r, v) => write!(f, "set {} {}", r, v),
+                Dec(r) => write!(f, "dec {}", r),
+                Out(v) => write!(f, "out {}", v),
+                Jnz(x, y) => write!(f, "jnz {} {}", x, y),
+            }
+        }
+    }
+
+    impl Encode for Countdown {
+        const NAME : &'static str = "countdown";
+
+        fn encode(&self) -> (u8, Vec<Value>) {
+            match *self {
+                Set(r, v) => (0, vec![Value::Reg(r), v]),
+                Dec(r) => (1, vec![Value::Reg(r)]),
+                Out(v) => (2, vec![v]),
+                Jnz(x, y) => (3, vec![x, y]),
+            }
+        }
+
+        fn decode_bytes(opcode : u8, args : &mut bytecode::Reader) -> Result<Countdown, String> {
+            Ok(match opcode {
+                0 => Set(args.register()?, args.value()?),
+                1 => Dec(args.register()?),
+                2 => Out(args.value()?),
+                3 => Jnz(args.value()?, args.value()?),
+                _ => return Err(args.unknown()),
+            })
+        }
+    }
+
+    impl Compile for Countdown {
+        fn compile(&self, ip : i64, mode : Arithmetic) -> compile::Op {
+            match *self {
+                Set(r, v) => compile::set(r, v),
+                Dec(r) => compile::binary(r, Value::Num(1), arith::BinOp::Sub, mode),
+                Out(v) => compile::send(v),
+                Jnz(x, y) => compile::jump_if(x, |x| x != 0, y, ip),
+            }
+        }
+    }
+
+    #[test]
+    fn test_run() {
+        let program = parse_program::<Countdown>("set a 3\n\nout a\ndec a\njnz a -2\n").unwrap();
+        assert_eq!(program[3], Jnz(Value::Reg('a'), Value::Num(-2)));
+        let mut process = Process::new(&program);
+        let mut sent = Vec::new();
+        loop {
+            match process.step() {
+                State::Sending(x) => sent.push(x),
+                State::Halted(ip) => {
+                    assert_eq!(ip, 4);
+                    break;
+                },
+                _ => { },
+            }
+        }
+        assert_eq!(sent, vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let input = "set a 3\nout a\ndec b\njnz a -200\nset b 1000000000000\nout -128\n";
+        let program = parse_program::<Countdown>(input).unwrap();
+        assert_eq!(disassemble(&program), input);
+        let bytes = bytecode::to_bytes(&program);
+        assert_eq!(&bytes[..16], b"aocvm\x01\x09countdown");
+        assert_eq!(bytes.len(), 16 + 4 + 4 + 2 + 2 + 11 + 11 + 3);
+        assert_eq!(bytecode::from_bytes::<Countdown>(&bytes), Ok(program));
+
+        let error = |bytes : &[u8]| bytecode::from_bytes::<Countdown>(bytes).unwrap_err();
+        assert_eq!(error(b"set a 3"), "not a bytecode file");
+        assert_eq!(error(b"aocvm\x02"), "unsupported bytecode version 2 (expected 1)");
+        assert_eq!(error(b"aocvm\x01\x04duet\x00\x00\x00\x00"), "bytecode is for duet programs, not countdown");
+        let header = b"aocvm\x01\x09countdown\x01\x00\x00\x00";
+        assert_eq!(error(&[&header[..], &[1, 2]].concat()), "invalid bytecode at byte 21: expected register");
+        assert_eq!(error(&[&header[..], &[4]].concat()), "invalid bytecode at byte 20: expected known opcode");
+        assert_eq!(error(&[&header[..], &[2, 0xff, 1]].concat()), "invalid bytecode at byte 22: expected number");
+        assert_eq!(error(&[&header[..], &[1, 0, 0]].concat()), "invalid bytecode at byte 22: expected end of file");
+    }
+
+    #[test]
+    fn test_compile() {
+        let program = parse_program::<Countdown>("set a 3\nset b -7\nout a\ndec a\njnz a -2\nout b\njnz 1 b").unwrap();
+        let code = Compiled::new(&program);
+        let mut runner = Runner::new(&code);
+        assert_eq!(runner.run(1), compile::Stop::OutOfSteps);
+        let mut sent = Vec::new();
+        loop {
+            match runner.run(100) {
+                compile::Stop::Sending(x) => sent.push(x),
+                stop => {
+                    assert_eq!(stop, compile::Stop::Halted(-1));
+                    break;
+                },
+            }
+        }
+        assert_eq!(sent, vec![3, 2, 1, -7]);
+        assert_eq!((runner.get_register('a'), runner.steps()), (0, 13));
+
+        let code = Compiled::new(&[Jnz(Value::Num(1), Value::Num(5))]);
+        assert_eq!(Runner::new(&code).run(10), compile::Stop::Halted(5));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |input| {
+            let e = parse_program::<Countdown>(input).unwrap_err();
+            (e.line, e.column, e.expected)
+        };
+        assert_eq!(error("set a 1\nset c 2"), (2, 5, String::from("register")));
+        assert_eq!(error("out x1"), (1, 5, String::from("register or number")));
+        assert_eq!(error("dec"), (1, 4, String::from("register")));
+        assert_eq!(error("dec a b"), (1, 7, String::from("end of line")));
+        assert_eq!(error("  inc a"), (1, 3, String::from("known keyword")));
+        assert_eq!(error("set a 1\n\nset b 2\njnz a c\nout a"), (4, 7, String::from("register or number")));
+    }
+
+    #[test]
+    fn test_labels() {
+        let input = "; counts down from 3\n\
+                     set a 3 ; a is the counter\n\
+                     top: out a\n\
+                     \x20   dec a\n\
+                     ; jumps back while a isn't 0\n\
+                     jnz a top\n\
+                     jnz 1 end\n\
+                     skipped: out a\n\
+                     end: done: ; halts\n";
+        let program = parse_program::<Countdown>(input).unwrap();
+        assert_eq!(disassemble(&program), "set a 3\nout a\ndec a\njnz a -2\njnz 1 2\nout a\n");
+        assert_eq!(parse_program::<Countdown>("jnz 1 b\nb2: jnz a b2"), Ok(vec![Jnz(Value::Num(1), Value::Reg('b')), Jnz(Value::Reg('a'), Value::Num(0))]));
+
+        let error = |input| {
+            let e = parse_program::<Countdown>(input).unwrap_err();
+            (e.line, e.column, e.expected)
+        };
+        assert_eq!(error("top: dec a\njnz a tpo"), (2, 7, String::from("defined label")));
+        assert_eq!(error("top: dec a\ntop: jnz a top"), (2, 1, String::from("new label ('top' is already defined)")));
+        assert_eq!(error("a: dec a"), (1, 1, String::from("label name")));
+        assert_eq!(error("dec a\n  2x: dec a"), (2, 3, String::from("label name")));
+        assert_eq!(error("jnz a 1 ; comment\nout top"), (2, 5, String::from("register or number")));
+    }
+
+    #[test]
+    fn test_debugger() {
+        let program = parse_program::<Countdown>("set a 3\nout a\ndec a\njnz a -2").unwrap();
+        let mut debugger = Debugger::new(Process::new(&program));
+        assert_eq!(debugger.command("break 3"), Some(String::from("breakpoint at line 3")));
+        assert_eq!(debugger.command("c").unwrap(), "breakpoint at line 3\nline 3: dec a");
+        assert_eq!(debugger.command("regs").unwrap(), "a=3 b=0");
+        assert_eq!(debugger.command("d 3").unwrap(), "deleted breakpoint at line 3");
+        assert_eq!(debugger.command("watch a").unwrap(), "watching a");
+        assert_eq!(debugger.command("continue").unwrap(), "watch a: 3 -> 2\nline 4: jnz a -2");
+        assert_eq!(debugger.command("step 2").unwrap(), "line 3: dec a");
+        assert_eq!(debugger.command("watch z").unwrap(), "expected a register from a to b");
+        assert_eq!(debugger.command("d a").unwrap(), "stopped watching a");
+        assert_eq!(debugger.command("c").unwrap(), "finished: halted at line 5\noutside the program (line 5)");
+        assert_eq!(debugger.command("q"), None);
+    }
+
+    #[test]
+    fn test_profile() {
+        let program = parse_program::<Countdown>("set a 3\nout a\ndec a\njnz a -2").unwrap();
+        let mut process = Process::new(&program);
+        process.enable_profile();
+        process.set_trace(Trace::new(2));
+        while let State::Ran(_) | State::Sending(_) = process.step() { }
+        assert_eq!(process.profile().unwrap().hits(), &[1, 3, 3, 3]);
+        assert_eq!(process.profile().unwrap().hottest(1), vec![(1, 3)]);
+        let trace = process.take_trace().unwrap();
+        assert_eq!(trace.steps(), 10);
+        assert_eq!(trace.recent().cloned().collect::<Vec<_>>(), vec![(2, Dec('a')), (3, Jnz(Value::Reg('a'), Value::Num(-2)))]);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        use arith::{Arithmetic, BinOp};
+
+        let big = Value::Num(i64::MAX);
+        let results = |mode| {
+            let mut registers = Registers::with_mode(mode);
+            registers.set('a', 2);
+            let effect = registers.mul('a', big);
+            (effect, registers.get(Value::Reg('a')))
+        };
+        assert_eq!(results(Arithmetic::Checked), (Effect::Fault(Fault::Overflow), 2));
+        assert_eq!(results(Arithmetic::Wrapping), (Effect::Next, -2));
+        assert_eq!(results(Arithmetic::Saturating), (Effect::Next, i64::MAX));
+        for &mode in &[Arithmetic::Checked, Arithmetic::Wrapping, Arithmetic::Saturating] {
+            assert_eq!(mode.apply(BinOp::Mod, 7, 0), Err(Fault::ModByZero));
+            assert_eq!(mode.apply(BinOp::Mod, -7, 3), Ok(-1));
+        }
+        assert_eq!(Arithmetic::Saturating.apply(BinOp::Mod, i64::MIN, -1), Ok(0));
+        assert_eq!(Arithmetic::Checked.apply(BinOp::Mod, i64::MIN, -1), Err(Fault::Overflow));
+        assert!(Arithmetic::parse("unchecked").is_err());
+
+        let program = parse_program::<Countdown>("set a -9223372036854775808\nout a\ndec a").unwrap();
+        let mut process = Process::new(&program);
+        assert_eq!((process.step(), process.step(), process.step()), (State::Ran(program[0]), State::Sending(i64::MIN), State::Faulted(2, Fault::Overflow)));
+        assert_eq!(process.step(), State::Faulted(2, Fault::Overflow));
+        let code = Compiled::new(&program);
+        let mut runner = Runner::new(&code);
+        assert_eq!((runner.run(10), runner.run(10)), (compile::Stop::Sending(i64::MIN), compile::Stop::Faulted(2, Fault::Overflow)));
+
+        // processes with different configs don't affect each other
+        let wrapping = Config { arithmetic: Arithmetic::Wrapping, ..Config::default() };
+        let mut process = Process::with_config(&program, wrapping);
+        while let State::Ran(_) | State::Sending(_) = process.step() { }
+        assert_eq!((process.get_register('a'), Process::new(&program).config()), (i64::MAX, Config::default()));
+        let code = Compiled::with_mode(&program, Arithmetic::Wrapping);
+        let mut runner = Runner::new(&code);
+        assert_eq!((runner.run(10), runner.run(10), runner.get_register('a')), (compile::Stop::Sending(i64::MIN), compile::Stop::Halted(3), i64::MAX));
+        assert_eq!(Termination::Faulted::<()> { ip: 2, fault: Fault::Overflow }.into_result(), Err(String::from("arithmetic overflow at line 3")));
+    }
+
+    #[test]
+    fn test_jump_overflow() {
+        let program = parse_program::<Countdown>("set a 1\njnz 1 9223372036854775807").unwrap();
+        let mut process = Process::new(&program);
+        assert_eq!(process.step(), State::Ran(program[0]));
+        assert_eq!(process.step(), State::Faulted(1, Fault::JumpOverflow));
+        assert_eq!(process.ip(), 1);
+        let code = Compiled::new(&program);
+        assert_eq!(Runner::new(&code).run(10), compile::Stop::Faulted(1, Fault::JumpOverflow));
+
+        let program = parse_program::<Countdown>("set b 9223372036854775807\njnz a 1\nset a 1\njnz a b").unwrap();
+        let code = Compiled::new(&program);
+        assert_eq!(Runner::new(&code).run(10), compile::Stop::Faulted(3, Fault::JumpOverflow));
+        assert_eq!(Termination::Faulted::<()> { ip: 3, fault: Fault::JumpOverflow }.into_result(), Err(String::from("jump target overflow at line 4")));
+    }
+
+    #[test]
+    fn test_watchdog() {
+        let run = |input, limits| {
+            let program = parse_program::<Countdown>(input).unwrap();
+            let mut process = Process::with_config(&program, Config { limits, ..Config::default() });
+            let mut watchdog = Watchdog::for_process(&process);
+            loop {
+                if let Some(termination) = watchdog.check(&process) {
+                    return termination;
+                }
+                match process.step() {
+                    State::Halted(ip) => return Termination::Halted(ip),
+                    State::Sending(_) => watchdog.io(),
+                    _ => { },
+                }
+            }
+        };
+        let detect = Limits { max_steps: None, detect_loops: true };
+        assert_eq!(run("set a 3\ndec a\njnz a -1", detect), Termination::Halted(3));
+        assert_eq!(run("set b 1\nset a 5\nset a 7\njnz b -2", detect), Termination::Looping { ip: 3, period: 3, steps: 6 });
+        assert_eq!(run("set a 1\nout a\njnz a -1", Limits { max_steps: Some(100), detect_loops: true }), Termination::Exhausted { steps: 100 });
+        assert_eq!(run("set a 1\njnz a 0", Limits { max_steps: Some(100), detect_loops: false }), Termination::Exhausted { steps: 100 });
+        assert_eq!(run("set a 1\njnz a 0", detect).into_result(), Err::<i64, _>(String::from("infinite loop at line 2: the state repeats every 1 steps (found after 2 steps)")));
+    }
+}
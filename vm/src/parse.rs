@@ -0,0 +1,135 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use util::ParseError;
+
+use {InstructionSet, Register, Value};
+
+/// The mnemonic and operands of one line of a program, for
+/// `InstructionSet::decode`. Errors point at the offending token.
+pub struct Operands<'a> {
+    input : &'a str,
+    line : &'a str,
+    words : Vec<&'a str>,
+    registers : usize,
+    used : Cell<usize>,
+    /// Index of the instruction, and of the instruction following each label.
+    ip : usize,
+    labels : &'a HashMap<&'a str, usize>,
+}
+
+impl<'a> Operands<'a> {
+    pub fn mnemonic(&self) -> &'a str { self.words[0] }
+
+    /// The error for an unknown mnemonic.
+    pub fn unknown(&self) -> ParseError {
+        ParseError::at(self.input, self.mnemonic(), "known keyword")
+    }
+
+    fn word(&self, i : usize, expected : &str) -> Result<&'a str, ParseError> {
+        self.used.set(usize::max(self.used.get(), i + 1));
+        self.words.get(i + 1).cloned()
+            .ok_or_else(|| ParseError::at(self.input, &self.line[self.line.len()..], expected))
+    }
+
+    fn to_register(&self, word : &str) -> Option<Register> { to_register(word, self.registers) }
+
+    /// The `i`th operand (counting from 0), which must be a register.
+    pub fn register(&self, i : usize) -> Result<Register, ParseError> {
+        let expected = "register";
+        let word = self.word(i, expected)?;
+        self.to_register(word).ok_or_else(|| ParseError::at(self.input, word, expected))
+    }
+
+    /// The `i`th operand (counting from 0), which may be a register or a number.
+    pub fn value(&self, i : usize) -> Result<Value, ParseError> {
+        let expected = "register or number";
+        let word = self.word(i, expected)?;
+        if let Some(r) = self.to_register(word) {
+            return Ok(Value::Reg(r));
+        }
+        word.parse().map(Value::Num).map_err(|_| ParseError::at(self.input, word, expected))
+    }
+
+    /// The `i`th operand (counting from 0) as the offset of a jump: a register,
+    /// a number, or a label, which is replaced by the offset to the instruction
+    /// following it.
+    pub fn offset(&self, i : usize) -> Result<Value, ParseError> {
+        let word = self.word(i, "register, number or label")?;
+        if ! is_label(word) {
+            return self.value(i);
+        }
+        match self.labels.get(word) {
+            Some(&target) => Ok(Value::Num(target as i64 - self.ip as i64)),
+            None => Err(ParseError::at(self.input, word, "defined label")),
+        }
+    }
+}
+
+/// The register named `word`, if there is one among the first `registers`.
+fn to_register(word : &str, registers : usize) -> Option<Register> {
+    let mut chars = word.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_lowercase() && ((c as u8 - b'a') as usize) < registers => Some(c),
+        _ => None,
+    }
+}
+
+/// True if `name` can be used as a label: a letter or `_` followed by letters,
+/// digits and `_`s, but not a single lowercase letter, which is a register name
+/// (even if the instruction set has fewer registers).
+fn is_label(name : &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_') && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && to_register(name, 26).is_none()
+}
+
+/// Parses a program with one instruction per line. Anything after `;` is a
+/// comment, and blank lines are ignored. A line may start with labels like
+/// `loop:`, which jumps (see `Operands::offset`) can use to refer to the
+/// instruction following them.
+pub fn parse_program<I : InstructionSet>(input : &str) -> Result<Vec<I>, ParseError> {
+    let mut labels = HashMap::new();
+    let mut lines = Vec::new();
+    for line in input.lines() {
+        let line = line.split(';').next().unwrap();
+        let mut words : Vec<&str> = line.split_whitespace().collect();
+        while let Some(label) = words.first().and_then(|word| word.strip_suffix(':')) {
+            if ! is_label(label) {
+                return Err(ParseError::at(input, label, "label name"));
+            }
+            if labels.insert(label, lines.len()).is_some() {
+                return Err(ParseError::at(input, label, format!("new label ('{}' is already defined)", label)));
+            }
+            words.remove(0);
+        }
+        if ! words.is_empty() {
+            lines.push((line, words));
+        }
+    }
+
+    let mut program = Vec::new();
+    for (ip, (line, words)) in lines.into_iter().enumerate() {
+        let operands = Operands {
+            input,
+            line,
+            words,
+            registers: I::REGISTERS,
+            used: Cell::new(0),
+            ip,
+            labels: &labels,
+        };
+        program.push(I::decode(&operands)?);
+        if let Some(extra) = operands.words.get(operands.used.get() + 1) {
+            return Err(ParseError::at(input, extra, "end of line"));
+        }
+    }
+    Ok(program)
+}
+
+/// Prints a program with one instruction per line, in the form `parse_program`
+/// reads.
+pub fn disassemble<I : Display>(program : &[I]) -> String {
+    program.iter().map(|instruction| format!("{}\n", instruction)).collect()
+}
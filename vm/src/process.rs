@@ -0,0 +1,189 @@
+use {Config, Effect, InstructionSet, Register, Value};
+use arith::{Arithmetic, BinOp, Fault};
+use trace::{Profile, Trace};
+use Value::*;
+
+/// The registers of a process, all starting at 0, and how arithmetic on them
+/// handles overflow.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Registers {
+    values : [i64; 26],
+    mode : Arithmetic,
+}
+
+macro_rules! bin_op {
+    ($name : ident, $op : expr) => {
+        /// Stores the result in `r`, or returns the fault that prevented it.
+        pub fn $name(&mut self, r : Register, v : Value) -> Effect {
+            let a = self.get(Reg(r));
+            let b = self.get(v);
+            match self.arith($op, a, b) {
+                Ok(x) => {
+                    self.set(r, x);
+                    Effect::Next
+                },
+                Err(fault) => Effect::Fault(fault),
+            }
+        }
+    }
+}
+
+impl Registers {
+    /// Registers with checked arithmetic.
+    pub fn new() -> Registers { Registers::with_mode(Arithmetic::default()) }
+
+    pub fn with_mode(mode : Arithmetic) -> Registers { Registers { values: [0; 26], mode } }
+
+    /// Position of a register, for `load` and `store`.
+    pub fn index(reg : Register) -> usize { (reg as u8 - b'a') as usize }
+
+    pub fn get(&self, val : Value) -> i64 {
+        match val {
+            Num(v) => v,
+            Reg(r) => self.values[Registers::index(r)]
+        }
+    }
+
+    pub fn set(&mut self, reg : Register, val : i64) {
+        self.values[Registers::index(reg)] = val;
+    }
+
+    /// The register at an index from `Registers::index`.
+    #[inline]
+    pub fn load(&self, index : usize) -> i64 { self.values[index] }
+
+    #[inline]
+    pub fn store(&mut self, index : usize, val : i64) { self.values[index] = val; }
+
+    /// Applies `op` to `a` and `b` in the mode of the registers.
+    #[inline]
+    pub fn arith(&self, op : BinOp, a : i64, b : i64) -> Result<i64, Fault> { self.mode.apply(op, a, b) }
+
+    bin_op!(add, BinOp::Add);
+    bin_op!(sub, BinOp::Sub);
+    bin_op!(mul, BinOp::Mul);
+    bin_op!(modulus, BinOp::Mod);
+}
+
+impl Default for Registers {
+    fn default() -> Registers { Registers::new() }
+}
+
+/// The result of a single step of a process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State<I> {
+    /// The instruction was executed.
+    Ran(I),
+    /// A value was sent.
+    Sending(i64),
+    /// The process is waiting for a value to store in the register, and won't
+    /// go on until it is given one with `Process::receive`.
+    Waiting(Register),
+    /// The instruction pointer is outside the program.
+    Halted(i64),
+    /// The instruction at the instruction pointer couldn't be executed. The
+    /// process won't go on.
+    Faulted(i64, Fault),
+}
+use self::State::*;
+
+/// A program running on its own registers.
+pub struct Process<'a, I : 'a> {
+    config : Config,
+    registers : Registers,
+    ip : i64,
+    program : &'a [I],
+    profile : Option<Profile>,
+    trace : Option<Trace<I>>,
+}
+
+impl<'a, I : InstructionSet> Process<'a, I> {
+    /// A process with the default config.
+    pub fn new(program : &'a [I]) -> Process<'a, I> {
+        Process::with_config(program, Config::default())
+    }
+
+    /// A process whose registers use the arithmetic of `config`, and whose
+    /// `Watchdog` (see `Watchdog::for_process`) checks its limits.
+    pub fn with_config(program : &'a [I], config : Config) -> Process<'a, I> {
+        let registers = Registers::with_mode(config.arithmetic);
+        Process { config, registers, ip: 0, program, profile: None, trace: None }
+    }
+
+    /// Executes the next instruction.
+    pub fn step(&mut self) -> State<I> {
+        if self.ip < 0 || self.ip >= self.program.len() as i64 {
+            return Halted(self.ip);
+        }
+        let instruction = self.program[self.ip as usize];
+        let effect = instruction.execute(&mut self.registers);
+        let next = match effect {
+            Effect::Next | Effect::Send(_) => self.ip + 1,
+            Effect::Jump(offset) => match self.ip.checked_add(offset) {
+                Some(ip) => ip,
+                None => return Faulted(self.ip, Fault::JumpOverflow),
+            },
+            Effect::Receive(r) => return Waiting(r),
+            Effect::Fault(fault) => return Faulted(self.ip, fault),
+        };
+        self.record();
+        self.ip = next;
+        match effect {
+            Effect::Send(x) => Sending(x),
+            _ => Ran(instruction),
+        }
+    }
+
+    /// Stores a value the process is waiting for, and moves past the instruction
+    /// that received it.
+    pub fn receive(&mut self, reg : Register, val : i64) {
+        self.registers.set(reg, val);
+        self.record();
+        self.ip += 1;
+    }
+
+    /// Counts the instruction at `ip` as executed.
+    fn record(&mut self) {
+        let ip = self.ip as usize;
+        if let Some(ref mut profile) = self.profile {
+            profile.record(ip);
+        }
+        if let Some(ref mut trace) = self.trace {
+            trace.record(ip, self.program[ip]);
+        }
+    }
+
+    /// Starts counting how many times each line is executed.
+    pub fn enable_profile(&mut self) {
+        self.profile = Some(Profile::new(self.program.len()));
+    }
+
+    pub fn profile(&self) -> Option<&Profile> { self.profile.as_ref() }
+
+    /// Starts recording executed instructions in `trace`.
+    pub fn set_trace(&mut self, trace : Trace<I>) {
+        self.trace = Some(trace);
+    }
+
+    pub fn trace(&self) -> Option<&Trace<I>> { self.trace.as_ref() }
+
+    /// Stops tracing, returning the trace so far.
+    pub fn take_trace(&mut self) -> Option<Trace<I>> { self.trace.take() }
+
+    pub fn get_register(&self, reg : Register) -> i64 {
+        self.registers.get(Reg(reg))
+    }
+
+    pub fn set_register(&mut self, reg : Register, val : i64) {
+        self.registers.set(reg, val);
+    }
+
+    pub fn registers(&self) -> &Registers { &self.registers }
+
+    /// Index of the next instruction.
+    pub fn ip(&self) -> i64 { self.ip }
+
+    pub fn program(&self) -> &'a [I] { self.program }
+
+    pub fn config(&self) -> Config { self.config }
+}
@@ -0,0 +1,156 @@
+use std::collections::VecDeque;
+use std::fmt::{Display, Write as FmtWrite};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use Machine;
+
+/// Number of times each line of a program was executed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    hits : Vec<u64>,
+}
+
+impl Profile {
+    pub fn new(lines : usize) -> Profile { Profile { hits: vec![0; lines] } }
+
+    pub fn record(&mut self, ip : usize) { self.hits[ip] += 1; }
+
+    /// Hit counts indexed by instruction (line number - 1).
+    pub fn hits(&self) -> &[u64] { &self.hits }
+
+    pub fn total(&self) -> u64 { self.hits.iter().sum() }
+
+    /// The `n` most executed instructions as `(index, hits)`, most executed first.
+    pub fn hottest(&self, n : usize) -> Vec<(usize, u64)> {
+        let mut lines : Vec<(usize, u64)> = self.hits.iter().cloned().enumerate().collect();
+        lines.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        lines.truncate(n);
+        lines
+    }
+
+    /// Adds the hits of another run of the same program.
+    pub fn merge(&mut self, other : &Profile) {
+        for (hits, more) in self.hits.iter_mut().zip(&other.hits) {
+            *hits += more;
+        }
+    }
+
+    /// Lists the program with the hits and share of the total for each line.
+    pub fn report<I : Display>(&self, program : &[I]) -> String {
+        let total = self.total().max(1) as f64;
+        let width = self.hits.iter().max().map_or(1, |h| h.to_string().len()).max(4);
+        let mut text = format!("line {:>w$}      %  instruction\n", "hits", w = width);
+        for (i, (instruction, &hits)) in program.iter().zip(&self.hits).enumerate() {
+            writeln!(text, "{:4} {:>w$} {:5.1}%  {}", i + 1, hits, 100.0 * hits as f64 / total, instruction, w = width).unwrap();
+        }
+        write!(text, "total {}", self.total()).unwrap();
+        text
+    }
+}
+
+/// A record of the instructions executed by a process: the last few in memory,
+/// and optionally every one in a file, one `step line: instruction` per line.
+pub struct Trace<I> {
+    recent : VecDeque<(usize, I)>,
+    capacity : usize,
+    file : Option<BufWriter<File>>,
+    error : Option<io::Error>,
+    steps : u64,
+}
+
+impl<I : Copy + Display> Trace<I> {
+    /// Keeps the last `capacity` instructions executed.
+    pub fn new(capacity : usize) -> Trace<I> {
+        Trace { recent: VecDeque::with_capacity(capacity), capacity, file: None, error: None, steps: 0 }
+    }
+
+    /// Also writes every instruction executed to a file.
+    pub fn with_file<P : AsRef<Path>>(mut self, path : P) -> io::Result<Trace<I>> {
+        self.file = Some(BufWriter::new(File::create(path)?));
+        Ok(self)
+    }
+
+    pub fn record(&mut self, ip : usize, instruction : I) {
+        self.steps += 1;
+        if self.capacity > 0 {
+            if self.recent.len() == self.capacity {
+                self.recent.pop_front();
+            }
+            self.recent.push_back((ip, instruction));
+        }
+        if let Some(ref mut file) = self.file {
+            if let Err(e) = writeln!(file, "{} {}: {}", self.steps, ip + 1, instruction) {
+                self.error = Some(e);
+            }
+        }
+        if self.error.is_some() {
+            self.file = None;
+        }
+    }
+
+    /// The last instructions executed as `(index, instruction)`, oldest first.
+    pub fn recent(&self) -> impl Iterator<Item = &(usize, I)> { self.recent.iter() }
+
+    pub fn steps(&self) -> u64 { self.steps }
+
+    /// Flushes the trace file, returning the first error writing to it.
+    pub fn finish(mut self) -> io::Result<()> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        match self.file {
+            Some(ref mut file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Number of recent instructions shown by `profile`.
+const RECENT : usize = 16;
+
+/// Runs a machine with every process profiled until it stops or has run
+/// `max_steps` steps, and reports the hit counts of each process along with
+/// the last instructions it executed. With `trace_file`, every instruction
+/// executed is also written to that file, or to `FILE.pN` for process N if
+/// there are several.
+pub fn profile<'a, M : Machine<'a>>(machine : &mut M, max_steps : Option<u64>, trace_file : Option<&str>) -> Result<String, String> {
+    let several = machine.processes().len() > 1;
+    for (pid, process) in machine.processes_mut().into_iter().enumerate() {
+        process.enable_profile();
+        let mut trace = Trace::new(RECENT);
+        if let Some(path) = trace_file {
+            let path = if several { format!("{}.p{}", path, pid) } else { path.to_string() };
+            trace = trace.with_file(&path).map_err(|e| format!("can't create trace file {}: {}", path, e))?;
+        }
+        process.set_trace(trace);
+    }
+
+    let mut steps = 0;
+    let stop = loop {
+        if max_steps.is_some_and(|max| steps >= max) {
+            break format!("stopped after {} steps", steps);
+        }
+        if let Some(reason) = machine.tick() {
+            break format!("finished after {} steps: {}", steps, reason);
+        }
+        steps += 1;
+    };
+
+    let mut text = String::new();
+    for (pid, process) in machine.processes_mut().into_iter().enumerate() {
+        if several {
+            writeln!(text, "process {}:", pid).unwrap();
+        }
+        writeln!(text, "{}", process.profile().unwrap().report(process.program())).unwrap();
+        let trace = process.take_trace().unwrap();
+        writeln!(text, "last {} instructions:", trace.recent().count()).unwrap();
+        for &(ip, ref instruction) in trace.recent() {
+            writeln!(text, "{:4}: {}", ip + 1, instruction).unwrap();
+        }
+        trace.finish().map_err(|e| format!("can't write trace file: {}", e))?;
+    }
+    text.push_str(&stop);
+    Ok(text)
+}
@@ -0,0 +1,143 @@
+//! Budgets and infinite-loop detection for running programs that might never
+//! stop.
+//!
+//! Loops are found on the instruction pointer and registers of a process: if
+//! they ever repeat exactly, with no value sent or received in between, the
+//! process will go around the same loop forever. The search follows the idea of
+//! Brent's algorithm, saving the state at every power of two steps, but it is
+//! a separate implementation from `util::cycle::brent`: that one runs the
+//! sequence itself, while a watchdog is checked one step at a time as the
+//! process runs, and starts over whenever it sends or receives a value.
+
+use util::args;
+
+use {Fault, InstructionSet, Process, Registers};
+
+/// Limits on how long a program may run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Limits {
+    /// Number of instructions after which to give up.
+    pub max_steps : Option<u64>,
+    /// Whether to look for states that repeat.
+    pub detect_loops : bool,
+}
+
+impl Limits {
+    /// The limits given with `--max-steps N` (or `--max-steps=N`) and
+    /// `--detect-loops` in the program arguments.
+    pub fn from_args() -> Result<Limits, String> {
+        Ok(Limits {
+            max_steps: args::parse_value_of("--max-steps", parse_max_steps)?,
+            detect_loops: args::is_present("--detect-loops"),
+        })
+    }
+}
+
+/// Parses a positive number of steps.
+pub fn parse_max_steps(steps : &str) -> Result<u64, String> {
+    match steps.parse() {
+        Ok(steps) if steps > 0 => Ok(steps),
+        _ => Err(format!("invalid number of steps: '{}'", steps)),
+    }
+}
+
+/// How running a program ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination<T> {
+    /// The program finished with a result.
+    Halted(T),
+    /// The program ran out of steps.
+    Exhausted { steps : u64 },
+    /// The program is in an infinite loop: after `steps` steps it was in the
+    /// same state as `period` steps before, at instruction `ip`.
+    Looping { ip : i64, period : u64, steps : u64 },
+    /// The instruction at `ip` couldn't be executed.
+    Faulted { ip : i64, fault : Fault },
+}
+
+impl<T> Termination<T> {
+    /// The result if the program halted, or an error saying why it didn't.
+    pub fn into_result(self) -> Result<T, String> {
+        match self {
+            Termination::Halted(result) => Ok(result),
+            Termination::Exhausted { steps } => Err(format!("gave up after {} steps", steps)),
+            Termination::Looping { ip, period, steps } =>
+                Err(format!("infinite loop at line {}: the state repeats every {} steps (found after {} steps)", ip + 1, period, steps)),
+            Termination::Faulted { ip, fault } => Err(format!("{} at line {}", fault, ip + 1)),
+        }
+    }
+
+    pub fn map<U, F : FnOnce(T) -> U>(self, f : F) -> Termination<U> {
+        match self {
+            Termination::Halted(result) => Termination::Halted(f(result)),
+            Termination::Exhausted { steps } => Termination::Exhausted { steps },
+            Termination::Looping { ip, period, steps } => Termination::Looping { ip, period, steps },
+            Termination::Faulted { ip, fault } => Termination::Faulted { ip, fault },
+        }
+    }
+}
+
+/// Checks a process against its limits before every step.
+#[derive(Debug, Clone)]
+pub struct Watchdog {
+    limits : Limits,
+    steps : u64,
+    /// The state the current one is compared with, if there was no I/O since.
+    saved : Option<(i64, Registers)>,
+    /// Steps since `saved`, and the number after which it's replaced.
+    since : u64,
+    power : u64,
+}
+
+impl Watchdog {
+    pub fn new(limits : Limits) -> Watchdog {
+        Watchdog { limits, steps: 0, saved: None, since: 0, power: 1 }
+    }
+
+    /// A watchdog with the limits the process was created with (see
+    /// `Process::with_config`).
+    pub fn for_process<'a, I : InstructionSet>(process : &Process<'a, I>) -> Watchdog {
+        Watchdog::new(process.config().limits)
+    }
+
+    /// Call before each step of the process. Returns how the run ends if the
+    /// process is out of steps or in a loop.
+    pub fn check<'a, I : InstructionSet, T>(&mut self, process : &Process<'a, I>) -> Option<Termination<T>> {
+        if self.limits.max_steps.is_some_and(|max| self.steps >= max) {
+            return Some(Termination::Exhausted { steps: self.steps });
+        }
+        self.steps += 1;
+        if ! self.limits.detect_loops {
+            return None;
+        }
+        let ip = process.ip();
+        match self.saved {
+            None => {
+                self.saved = Some((ip, process.registers().clone()));
+                self.since = 0;
+                self.power = 1;
+            },
+            Some((saved_ip, ref registers)) => {
+                self.since += 1;
+                if saved_ip == ip && registers == process.registers() {
+                    return Some(Termination::Looping { ip, period: self.since, steps: self.steps - 1 });
+                }
+                if self.since == self.power {
+                    self.saved = Some((ip, process.registers().clone()));
+                    self.since = 0;
+                    self.power *= 2;
+                }
+            },
+        }
+        None
+    }
+
+    /// Call when the process sends or receives a value, since a loop with I/O
+    /// can still end.
+    pub fn io(&mut self) {
+        self.saved = None;
+    }
+
+    /// Number of steps checked so far.
+    pub fn steps(&self) -> u64 { self.steps }
+}